@@ -1,7 +1,7 @@
 use std::{
     env,
-    io::{Error, ErrorKind},
-    os::unix::process::CommandExt,
+    io::Error,
+    os::unix::process::{CommandExt, ExitStatusExt},
     process::{Child, Command, Stdio},
     time::Duration,
 };
@@ -9,75 +9,119 @@ use std::{
 use crate::{
     config::ConfigApp,
     fs::open_file,
-    readiness_probe,
+    process::detach_from_terminal,
+    readiness_probe::{self, PendingCheck},
+    reaper,
     utils::{get_now, normalize_path},
 };
 
-use super::{AppReadinessProbe, AppStatus};
+use super::{
+    AppDependencyCondition, AppFailurePolicy, AppLivenessAction, AppLivenessProbe,
+    AppOverlapPolicy, AppReadinessProbe, AppRestartBackoff, AppRestartPolicy, AppSchedule,
+    AppStartLimit, AppStatus, AppStopAction, AppStopStep, AppStopper, AppType,
+};
+
+/*
+ * Processes left in the process group of an exited app are not children of maestro,
+ * so there is no event when they exit and they have to be polled.
+ * A running pre-stop command is polled too, the stop sequence waits for it without a deadline
+ */
+const POLL_PERIOD: u64 = 100;
+
+/*
+ * Starts the check of a `command` or `http` probe on its own thread
+ */
+fn start_check(probe: &AppReadinessProbe, timeout: u32) -> Option<PendingCheck> {
+    match probe {
+        AppReadinessProbe::Command { command, .. } => {
+            let command = command.to_owned();
+
+            Some(PendingCheck::spawn(move || {
+                readiness_probe::command(command, timeout)
+            }))
+        }
+        AppReadinessProbe::Http { url, method, .. } => {
+            let (url, method) = (url.to_owned(), method.to_owned());
+
+            Some(PendingCheck::spawn(move || {
+                readiness_probe::http(method, url, timeout)
+            }))
+        }
+        _ => None,
+    }
+}
 
 #[derive(Debug)]
 pub struct App {
     name: String,
     command: Vec<String>,
+    app_type: AppType,
+    timeout: Option<u32>,
     uid: u32,
     ready: bool,
     readiness_probe: AppReadinessProbe,
-    signal: i32,
+    liveness_probe: Option<AppLivenessProbe>,
+    restart_policy: AppRestartPolicy,
+    restart_backoff: AppRestartBackoff,
+    start_limit: AppStartLimit,
+    failure_policy: AppFailurePolicy,
+    success_exit_codes: Vec<i32>,
+    schedule: Option<AppSchedule>,
+    overlap_policy: AppOverlapPolicy,
+    process_group: bool,
+    forward_signals: Vec<i32>,
 
     process: Option<Child>,
+    stopper: AppStopper,
     status: AppStatus,
     exit_code: Option<i32>,
+    exit_signal: Option<i32>,
+    restarts: u32,
 
     started_at: Option<Duration>,
     updated_at: Duration,
     ready_checked_at: Option<Duration>,
+    ready_check: Option<PendingCheck>,
+    ready_at: Option<Duration>,
+    liveness_checked_at: Option<Duration>,
+    liveness_check: Option<PendingCheck>,
+    liveness_failures: u32,
+    liveness_failed: bool,
+    restarted_at: Vec<Duration>,
+    restart_at: Option<Duration>,
+    stop_requested: bool,
+    timed_out: bool,
+    schedule_checked_at: Option<Duration>,
+    queued: bool,
 
     stdout: Option<String>,
     stderr: Option<String>,
 }
 
 impl App {
-    pub fn new(
-        name: String,
-        command: Vec<String>,
-        uid: u32,
-        readiness_probe: AppReadinessProbe,
-        signal: i32,
-
-        stdout: Option<String>,
-        stderr: Option<String>,
-    ) -> Self {
-        let app = Self {
-            name: name.to_owned(),
-            command,
-            uid,
-            readiness_probe,
-            signal,
-            stdout,
-            stderr,
-
-            process: None,
-            status: AppStatus::Init,
-            ready: false,
-            exit_code: None,
-
-            started_at: None,
-            updated_at: get_now(),
-            ready_checked_at: None,
-        };
-
-        log::info!("app \"{}\" created", name);
+    fn set_status(&mut self, status: AppStatus) {
+        /*
+         * Results of checks started before the change are stale
+         */
+        self.ready_check = None;
+        self.liveness_check = None;
 
-        app
-    }
+        /*
+         * An app that is not running is not ready, whatever the reason.
+         * Apps that are expected to exit become ready again once stopped (see `update_readiness`)
+         */
+        if status != AppStatus::Running {
+            self.ready = false;
+            self.ready_at = None;
+        }
 
-    fn set_status(&mut self, status: AppStatus) {
         self.status = status;
         log::info!("app \"{}\" status changed to {}", self.name, status);
     }
 
     fn set_ready(&mut self) {
         self.ready = true;
+        self.ready_at = Some(get_now());
         log::info!("app \"{}\" is READY now", self.name);
     }
 
@@ -89,10 +133,6 @@ impl App {
         self.status
     }
 
-    pub fn get_exit_code(&self) -> Option<i32> {
-        self.exit_code
-    }
-
     pub fn get_pid(&self) -> Option<u32> {
         if let Some(p) = &self.process {
             return Some(p.id());
@@ -101,10 +141,132 @@ impl App {
         None
     }
 
+    pub fn get_type(&self) -> AppType {
+        self.app_type
+    }
+
+    pub fn is_scheduled(&self) -> bool {
+        self.schedule.is_some()
+    }
+
+    pub fn is_queued(&self) -> bool {
+        self.queued
+    }
+
+    pub fn get_restart_policy(&self) -> AppRestartPolicy {
+        self.restart_policy
+    }
+
+    pub fn get_failure_policy(&self) -> AppFailurePolicy {
+        self.failure_policy
+    }
+
     pub fn is_ready(&self) -> bool {
         self.ready
     }
 
+    fn has_exited(&self) -> bool {
+        self.exit_code.is_some() || self.exit_signal.is_some()
+    }
+
+    /*
+     * Signals are sent to the whole process group (negative pid) if the app has its own group
+     */
+    fn get_signal_target(&self) -> Result<i32, Error> {
+        let pid: i32 = self
+            .get_pid()
+            .ok_or(Error::other("unable to get pid"))?
+            .try_into()
+            .map_err(|_| Error::other("unable to get pid"))?;
+
+        Ok(if self.process_group { -pid } else { pid })
+    }
+
+    /*
+     * There are processes left in the app's process group (e.g. started by a wrapper script)
+     */
+    fn is_group_alive(&self) -> bool {
+        if !self.process_group {
+            return false;
+        }
+
+        self.get_signal_target()
+            .is_ok_and(|target| unsafe { libc::kill(target, 0) } == 0)
+    }
+
+    fn has_succeeded(&self) -> bool {
+        self.exit_code
+            .is_some_and(|x| self.success_exit_codes.contains(&x))
+    }
+
+    /*
+     * The app is considered failed if it has stopped with a code not listed in `success_exit_codes`
+     * or if it was not possible to get the code at all.
+     * An app that has been restarted too many times is failed too
+     */
+    pub fn has_failed(&self) -> bool {
+        match self.status {
+            AppStatus::Failed => true,
+            AppStatus::Stopped => !self.has_succeeded(),
+            _ => false,
+        }
+    }
+
+    /*
+     * The app is not alive and its liveness probe demands to stop all apps
+     */
+    pub fn should_shutdown(&self) -> bool {
+        self.liveness_failed
+            && self
+                .liveness_probe
+                .as_ref()
+                .is_some_and(|x| x.action == AppLivenessAction::Shutdown)
+    }
+
+    /*
+     * Checks the condition of a dependency edge pointing to the app.
+     * Only a running app is started or ready, except apps that are expected to exit
+     * (oneshot apps and apps with the `exit_code` probe): they are ready once completed
+     */
+    pub fn satisfies(&self, condition: AppDependencyCondition) -> bool {
+        let completed = self.status == AppStatus::Stopped && self.ready;
+
+        match condition {
+            AppDependencyCondition::ServiceStarted => {
+                self.status == AppStatus::Running || completed
+            }
+            AppDependencyCondition::ServiceReady => {
+                self.status == AppStatus::Running && self.ready || completed
+            }
+            AppDependencyCondition::CompletedSuccessfully => {
+                self.status == AppStatus::Stopped && self.has_succeeded()
+            }
+        }
+    }
+
+    pub fn should_restart(&self) -> bool {
+        if self.status != AppStatus::Stopped || self.started_at.is_none() {
+            /*
+             * An app that could not even be spawned will not be fixed by a restart
+             */
+            return false;
+        }
+
+        /*
+         * The app has been stopped because it is not alive
+         */
+        if self.liveness_failed {
+            return true;
+        }
+
+        match self.restart_policy {
+            AppRestartPolicy::Never => false,
+            AppRestartPolicy::OnFailure => self.has_failed(),
+            AppRestartPolicy::Always => true,
+            AppRestartPolicy::UnlessStopped => !self.stop_requested,
+        }
+    }
+
     fn redirect_stdio(&self, to: Option<String>) -> Stdio {
         if to.to_owned().is_some_and(|x| x == "inherit") {
             return Stdio::inherit();
@@ -137,14 +299,21 @@ impl App {
         let args = full_command;
         let envs = env::vars();
 
-        let result = Command::new(executable)
+        let mut command = Command::new(executable);
+
+        command
             .envs(envs)
             .args(args)
             .uid(self.uid)
             .stdin(Stdio::null())
             .stdout(self.redirect_stdio(self.stdout.to_owned()))
-            .stderr(self.redirect_stdio(self.stderr.to_owned()))
-            .spawn();
+            .stderr(self.redirect_stdio(self.stderr.to_owned()));
+
+        /*
+         * Every app is the leader of its own process group,
+         * so everything it starts can be signalled at once if `process_group` is enabled
+         */
+        let result = reaper::spawn(detach_from_terminal(&mut command));
 
         match result {
             Ok(child) => {
@@ -167,10 +336,26 @@ impl App {
         }
     }
 
+    /*
+     * Only apps that are expected to exit (oneshot apps and apps with the `exit_code` probe)
+     * can become ready after they have stopped
+     */
+    fn is_readiness_checked(&self) -> bool {
+        let on_exit = self.app_type == AppType::Oneshot
+            || matches!(self.readiness_probe, AppReadinessProbe::ExitCode { .. });
+
+        match self.status {
+            AppStatus::Running => true,
+            AppStatus::Stopping | AppStatus::Stopped => on_exit,
+            AppStatus::Init | AppStatus::Backoff | AppStatus::Failed => false,
+        }
+    }
+
     fn update_readiness(&mut self) {
-        if self.status == AppStatus::Init || self.ready {
+        if !self.is_readiness_checked() || self.ready {
             /*
-             * For an app to be considered ready, it must at least be RUNNING
+             * For an app to be considered ready, it must at least be RUNNING.
+             * An app waiting to be restarted or given up is not ready either
              */
             return;
         }
@@ -178,53 +363,42 @@ impl App {
         let now = get_now();
 
         match &self.readiness_probe {
-            AppReadinessProbe::Command { command, period } => match self.ready_checked_at {
-                None => {
-                    self.ready_checked_at = Some(now);
+            AppReadinessProbe::Command {
+                period, timeout, ..
+            }
+            | AppReadinessProbe::Http {
+                period, timeout, ..
+            } => {
+                if let Some(result) = self.ready_check.as_ref().and_then(|x| x.poll()) {
+                    self.ready_check = None;
 
-                    if readiness_probe::command(command.to_owned()) {
+                    if result {
                         self.set_ready();
-                    }
-                }
-                Some(last_ready_checked) => {
-                    if now.as_millis() - last_ready_checked.as_millis() >= *period as u128 {
-                        self.ready_checked_at = Some(now);
 
-                        if readiness_probe::command(command.to_owned()) {
-                            self.set_ready();
-                        }
-                    }
-                }
-            },
-            AppReadinessProbe::Delay { delay } => match self.started_at {
-                None => (),
-                Some(started) => {
-                    if now.as_millis() - started.as_millis() >= *delay as u128 {
-                        self.set_ready();
+                        return;
                     }
                 }
-            },
-            AppReadinessProbe::Http {
-                url,
-                method,
-                period,
-            } => match self.ready_checked_at {
-                None => {
-                    self.ready_checked_at = Some(now);
 
-                    if readiness_probe::http(method.to_owned(), url.to_owned()) {
-                        self.set_ready()
-                    }
-                }
-                Some(last_ready_checked) => {
-                    if now.as_millis() - last_ready_checked.as_millis() >= *period as u128 {
-                        self.ready_checked_at = Some(now);
+                /*
+                 * A failed check that took longer than the period is repeated right away
+                 */
 
-                        if readiness_probe::http(method.to_owned(), url.to_owned()) {
-                            self.set_ready();
-                        }
-                    }
+                let due = self
+                    .ready_checked_at
+                    .is_none_or(|x| now.saturating_sub(x) >= Duration::from_millis(*period as u64));
+
+                if due && self.ready_check.is_none() {
+                    self.ready_checked_at = Some(now);
+                    self.ready_check = start_check(&self.readiness_probe, *timeout);
                 }
+            }
+            AppReadinessProbe::Delay { delay } => match self.started_at {
+                Some(started)
+                    if now.saturating_sub(started) >= Duration::from_millis(*delay as u64) =>
+                {
+                    self.set_ready();
+                }
+                _ => (),
             },
             AppReadinessProbe::ExitCode { exit_code } => {
                 if self.status == AppStatus::Stopped
@@ -233,6 +407,16 @@ impl App {
                     self.set_ready();
                 }
             }
+            AppReadinessProbe::None if self.app_type == AppType::Oneshot => {
+                if self.status == AppStatus::Stopped && self.has_succeeded() {
+                    log::info!(
+                        "oneshot app \"{}\" has completed successfully, considering as READY",
+                        self.name
+                    );
+
+                    self.set_ready();
+                }
+            }
             AppReadinessProbe::None => {
                 log::info!(
                     "no readiness probe is presented for app \"{}\", considering as READY",
@@ -244,10 +428,186 @@ impl App {
         }
     }
 
+    /*
+     * Returns the nearest moment (since UNIX epoch) after `after` the app must be updated at,
+     * even if nothing happens. `None` means the app is waiting for an event
+     * (exit of a child, completion of a check, etc.)
+     */
+    pub fn get_next_deadline(&self, after: Duration) -> Option<Duration> {
+        let ms = |x: u32| Duration::from_millis(x as u64);
+        let mut deadlines: Vec<Duration> = vec![];
+
+        match self.status {
+            AppStatus::Backoff => deadlines.extend(self.restart_at),
+            AppStatus::Running if !self.timed_out => {
+                if let (Some(started), Some(timeout)) = (self.started_at, self.timeout) {
+                    deadlines.push(started + ms(timeout));
+                }
+            }
+            AppStatus::Stopping => {
+                deadlines.extend(self.stopper.get_deadlines());
+
+                let waiting_for_group = self.stopper.is_waiting() && self.has_exited();
+                let waiting_for_pre_stop =
+                    self.stopper.is_pre_stopping() && self.stopper.has_command();
+
+                if waiting_for_group || waiting_for_pre_stop {
+                    deadlines.push(get_now() + Duration::from_millis(POLL_PERIOD));
+                }
+            }
+            _ => (),
+        }
+
+        let readiness_checked = self.is_readiness_checked() && !self.ready;
+
+        match self.readiness_probe {
+            AppReadinessProbe::Delay { delay } if readiness_checked => {
+                deadlines.extend(self.started_at.map(|x| x + ms(delay)));
+            }
+            AppReadinessProbe::Command { period, .. } | AppReadinessProbe::Http { period, .. }
+                if readiness_checked && self.ready_check.is_none() =>
+            {
+                deadlines.extend(self.ready_checked_at.map(|x| x + ms(period)));
+            }
+            _ => (),
+        }
+
+        if let (Some(liveness), Some(ready_at)) = (&self.liveness_probe, self.ready_at) {
+            let period = match liveness.probe {
+                AppReadinessProbe::Command { period, .. } => Some(period),
+                AppReadinessProbe::Http { period, .. } => Some(period),
+                _ => None,
+            };
+
+            if let Some(period) = period.filter(|_| {
+                self.status == AppStatus::Running
+                    && self.ready
+                    && !self.liveness_failed
+                    && self.liveness_check.is_none()
+            }) {
+                deadlines.push(match self.liveness_checked_at {
+                    None => ready_at + ms(liveness.initial_delay),
+                    Some(checked) => checked + ms(period),
+                });
+            }
+        }
+
+        match &self.schedule {
+            Some(AppSchedule::Interval(interval)) => {
+                deadlines.extend(self.schedule_checked_at.map(|x| x + ms(*interval)));
+            }
+            Some(AppSchedule::Cron(_)) => {
+                // the beginning of the next minute
+                deadlines.push(Duration::from_secs((get_now().as_secs() / 60 + 1) * 60));
+            }
+            None => (),
+        }
+
+        deadlines.into_iter().filter(|x| *x > after).min()
+    }
+
+    /*
+     * Unlike the readiness probe, the liveness probe is checked continuously
+     * once the app has become ready
+     */
+    fn update_liveness(&mut self) {
+        if let Some(alive) = self.liveness_check.as_ref().and_then(|x| x.poll()) {
+            self.liveness_check = None;
+            self.on_liveness_checked(alive);
+        }
+
+        if self.status != AppStatus::Running || !self.ready || self.liveness_failed {
+            return;
+        }
+
+        let (Some(liveness), Some(ready_at)) = (&self.liveness_probe, self.ready_at) else {
+            return;
+        };
+
+        let now = get_now();
+
+        let period = match liveness.probe {
+            AppReadinessProbe::Command { period, .. } => period,
+            AppReadinessProbe::Http { period, .. } => period,
+            _ => return,
+        };
+
+        /*
+         * A check that took longer than the period is repeated right away
+         */
+        let due = match self.liveness_checked_at {
+            None => {
+                now.saturating_sub(ready_at) >= Duration::from_millis(liveness.initial_delay as u64)
+            }
+            Some(checked) => now.saturating_sub(checked) >= Duration::from_millis(period as u64),
+        };
+
+        if due && self.liveness_check.is_none() {
+            self.liveness_checked_at = Some(now);
+            self.liveness_check = start_check(&liveness.probe, liveness.timeout);
+        }
+    }
+
+    fn on_liveness_checked(&mut self, alive: bool) {
+        let Some(liveness) = &self.liveness_probe else {
+            return;
+        };
+
+        let threshold = liveness.failure_threshold;
+        let action = liveness.action;
+
+        if alive {
+            if self.liveness_failures > 0 {
+                log::info!("app \"{}\" is alive again", self.name);
+            }
+
+            self.liveness_failures = 0;
+            return;
+        }
+
+        self.liveness_failures += 1;
+
+        log::warn!(
+            "liveness check of app \"{}\" has failed ({} of {})",
+            self.name,
+            self.liveness_failures,
+            threshold
+        );
+
+        if self.liveness_failures < threshold {
+            return;
+        }
+
+        self.liveness_failures = 0;
+
+        match action {
+            AppLivenessAction::Log => {
+                log::error!("app \"{}\" is not alive, ignoring", self.name);
+            }
+            AppLivenessAction::Restart => {
+                log::error!("app \"{}\" is not alive, restarting it", self.name);
+
+                self.liveness_failed = true;
+                self.stop();
+            }
+            AppLivenessAction::Shutdown => {
+                log::error!("app \"{}\" is not alive, stopping all apps", self.name);
+
+                self.liveness_failed = true;
+            }
+        }
+    }
+
     pub fn update(&mut self) {
-        if self.status != AppStatus::Stopped && self.exit_code.is_none() {
+        if self.status != AppStatus::Stopped && !self.has_exited() {
             if let Some(process) = &mut self.process {
-                match process.try_wait() {
+                let result = process.try_wait();
+
+                if !matches!(result, Ok(None)) {
+                    reaper::release(process.id());
+                }
+
+                match result {
                     Err(err) => {
                         log::error!(
                             "unable to check the app \"{}\", {}",
@@ -259,27 +619,318 @@ impl App {
                     Ok(exit_status) => {
                         if let Some(es) = exit_status {
                             self.exit_code = es.code();
+                            self.exit_signal = es.signal();
 
                             if let Some(code) = self.exit_code {
                                 log::info!("app \"{}\" exited with code {}", self.name, code);
                             }
 
-                            self.set_status(AppStatus::Stopped);
+                            if let Some(signal) = self.exit_signal {
+                                log::info!(
+                                    "app \"{}\" was terminated by signal {}",
+                                    self.name,
+                                    signal
+                                );
+                            }
+
+                            if self.schedule.is_some() {
+                                if self.has_succeeded() {
+                                    log::info!(
+                                        "scheduled run of app \"{}\" has succeeded",
+                                        self.name
+                                    );
+                                } else {
+                                    log::warn!("scheduled run of app \"{}\" has failed", self.name);
+                                }
+                            }
+
+                            if self.status == AppStatus::Stopping
+                                && self.stopper.is_waiting()
+                                && self.is_group_alive()
+                            {
+                                log::info!(
+                                    "app \"{}\" has exited, waiting for the rest of its process group",
+                                    self.name
+                                );
+                            } else {
+                                if self.is_group_alive() {
+                                    log::warn!(
+                                        "app \"{}\" has exited, killing the rest of its process group",
+                                        self.name
+                                    );
+                                    self.kill();
+                                }
+
+                                self.set_status(AppStatus::Stopped);
+                            }
                         }
                     }
                 }
             }
         }
 
+        /*
+         * After the escalation to SIGKILL there is nothing left to wait for
+         */
+        if self.status == AppStatus::Stopping
+            && self.has_exited()
+            && (!self.is_group_alive() || !self.stopper.is_waiting())
+        {
+            self.set_status(AppStatus::Stopped);
+        }
+
+        if self.status == AppStatus::Backoff && self.restart_at.is_some_and(|x| get_now() >= x) {
+            self.reset();
+        }
+
+        /*
+         * The stop command is reaped first, the end of the pre-stop hook depends on it
+         */
+        self.update_timeout();
+        self.update_stop_command();
+        self.update_pre_stop();
+        self.update_stop_timeout();
+        self.update_schedule();
+
         /*
          * The readiness update must come after the state update
          * because it can depend on the state, but not vice versa
          */
         self.update_readiness();
+        self.update_liveness();
         self.updated_at = get_now();
     }
 
+    /*
+     * Schedules a restart of the stopped app. The app waits in BACKOFF
+     * and then goes back to INIT, so it will be started again
+     * as soon as its dependencies are ready
+     */
+    pub fn restart(&mut self) {
+        if self.status != AppStatus::Stopped {
+            return;
+        }
+
+        let now = get_now();
+        let burst = self.start_limit.burst;
+        let interval = self.start_limit.interval;
+
+        self.restarted_at
+            .retain(|x| now.saturating_sub(*x) < Duration::from_millis(interval as u64));
+
+        if self.restarted_at.len() as u32 >= burst {
+            log::error!(
+                "app \"{}\" has been restarted {} times within {} ms, giving up",
+                self.name,
+                burst,
+                interval
+            );
+            self.set_status(AppStatus::Failed);
+
+            return;
+        }
+
+        self.restarted_at.push(now);
+        self.restarts += 1;
+
+        let attempt = self.restarted_at.len() as u32;
+        let delay = self.restart_backoff.get_delay(attempt);
+
+        self.restart_at = Some(now + Duration::from_millis(delay as u64));
+
+        log::info!(
+            "app \"{}\" will be restarted in {} ms, restart #{} ({} of {} restarts left within {} ms)",
+            self.name,
+            delay,
+            self.restarts,
+            burst - attempt,
+            burst,
+            interval
+        );
+        self.set_status(AppStatus::Backoff);
+    }
+
+    /*
+     * Gives up on the scheduled restart, the app stays STOPPED
+     */
+    pub fn cancel_restart(&mut self) {
+        if self.status != AppStatus::Backoff {
+            return;
+        }
+
+        self.restart_at = None;
+
+        log::info!("restart of app \"{}\" is cancelled", self.name);
+        self.set_status(AppStatus::Stopped);
+    }
+
+    /*
+     * Brings the app back to INIT, so it will be started again
+     * as soon as its dependencies are ready
+     */
+    pub fn reset(&mut self) {
+        self.process = None;
+        self.exit_code = None;
+        self.exit_signal = None;
+        self.ready = false;
+        self.started_at = None;
+        self.ready_checked_at = None;
+        self.ready_check = None;
+        self.ready_at = None;
+        self.liveness_checked_at = None;
+        self.liveness_check = None;
+        self.liveness_failures = 0;
+        self.liveness_failed = false;
+        self.restart_at = None;
+        self.timed_out = false;
+        self.stop_requested = false;
+
+        self.set_status(AppStatus::Init);
+    }
+
+    fn update_schedule(&mut self) {
+        let now = get_now();
+        let previous = self.schedule_checked_at;
+
+        let due = match &self.schedule {
+            None => return,
+            Some(AppSchedule::Interval(interval)) => previous
+                .is_none_or(|x| now.saturating_sub(x) >= Duration::from_millis(*interval as u64)),
+            Some(AppSchedule::Cron(expression)) => {
+                /*
+                 * Every minute is checked once. The minute maestro has started in is skipped
+                 * because it has already partially passed
+                 */
+                self.schedule_checked_at = Some(now);
+
+                previous.is_some_and(|x| x.as_secs() / 60 != now.as_secs() / 60)
+                    && expression.matches(now)
+            }
+        };
+
+        if !due {
+            return;
+        }
+
+        self.schedule_checked_at = Some(now);
+
+        if ![AppStatus::Running, AppStatus::Stopping].contains(&self.status) {
+            self.queued = true;
+
+            return;
+        }
+
+        match self.overlap_policy {
+            AppOverlapPolicy::Skip => {
+                log::warn!(
+                    "previous run of app \"{}\" is still in progress, skipping",
+                    self.name
+                );
+            }
+            AppOverlapPolicy::Queue => {
+                log::info!(
+                    "previous run of app \"{}\" is still in progress, the next run is queued",
+                    self.name
+                );
+
+                self.queued = true;
+            }
+            AppOverlapPolicy::KillPrevious => {
+                log::warn!(
+                    "previous run of app \"{}\" is still in progress, stopping it",
+                    self.name
+                );
+
+                self.queued = true;
+                self.stop();
+            }
+        }
+    }
+
+    /*
+     * Starts the queued run of the scheduled app
+     */
+    pub fn run_scheduled(&mut self) {
+        if !self.queued || !self.status.is_inactive() {
+            return;
+        }
+
+        self.queued = false;
+
+        if self.status != AppStatus::Init {
+            self.reset();
+        }
+
+        log::info!("starting scheduled run of app \"{}\"", self.name);
+        self.run();
+    }
+
+    fn update_timeout(&mut self) {
+        if self.status != AppStatus::Running || self.timed_out {
+            return;
+        }
+
+        let (Some(started), Some(timeout)) = (self.started_at, self.timeout) else {
+            return;
+        };
+
+        if get_now().saturating_sub(started) >= Duration::from_millis(timeout as u64) {
+            log::error!(
+                "oneshot app \"{}\" has not completed within {} ms",
+                self.name,
+                timeout
+            );
+
+            self.timed_out = true;
+            self.kill();
+        }
+    }
+
+    fn update_stop_timeout(&mut self) {
+        if self.status != AppStatus::Stopping {
+            return;
+        }
+
+        match self.stopper.update_timeout(&self.name) {
+            Some(AppStopAction::RunStep) => self.run_stop_step(),
+            Some(AppStopAction::Kill) => self.kill(),
+            None => (),
+        }
+    }
+
+    /*
+     * Kills the app immediately, without waiting for the stop timeout.
+     * Returns `true` if there was something to kill
+     */
+    pub fn force_kill(&mut self) -> bool {
+        if !matches!(self.status, AppStatus::Running | AppStatus::Stopping) {
+            return false;
+        }
+
+        self.stopper.escalate();
+        self.stop_requested = true;
+        self.kill();
+
+        if self.status == AppStatus::Running {
+            self.set_status(AppStatus::Stopping);
+        }
+
+        true
+    }
+
     fn kill(&mut self) {
+        if self.process_group {
+            if let Ok(target) = self.get_signal_target() {
+                log::info!(
+                    "killing process group of app \"{}\" with SIGKILL...",
+                    self.name
+                );
+                unsafe { libc::kill(target, libc::SIGKILL) };
+            }
+
+            return;
+        }
+
         if let Some(ref mut proc) = self.process {
             log::info!("killing app \"{}\" with SIGKILL...", self.name);
             proc.kill().ok();
@@ -291,39 +942,166 @@ impl App {
             return;
         }
 
-        let exec_kill = || -> Result<i32, Error> {
-            let pid: i32 = self
-                .get_pid()
-                .ok_or(Error::new(ErrorKind::Other, "unable to get pid"))?
-                .try_into()
-                .map_err(|_| Error::new(ErrorKind::Other, "unable to get pid"))?;
+        self.stop_requested = true;
+        self.set_status(AppStatus::Stopping);
 
-            // On success (at least one signal was sent), zero is returned.  On
-            // error, -1 is returned
-            let code = unsafe { libc::kill(pid, self.signal) };
+        /*
+         * Oneshot apps are never signalled, they are given the stop timeout to complete
+         * and then killed
+         */
+        self.stopper.start(self.app_type == AppType::Oneshot);
 
-            Ok(code)
-        };
+        if self.app_type == AppType::Oneshot {
+            return;
+        }
 
-        match exec_kill() {
-            Ok(status) => {
-                if status != 0 {
-                    log::warn!("unable to kill the app \"{}\" gracefully", self.name);
-                    self.kill();
-                } else {
-                    self.set_status(AppStatus::Stopping);
-                }
-            }
-            Err(err) => {
+        if !self.stopper.is_pre_stopping() {
+            self.run_stop_step();
+            return;
+        }
+
+        /*
+         * The app keeps running while the pre-stop hook is in progress
+         * (e.g. to let a load balancer notice it's leaving)
+         */
+        if let Some(pre_stop) = self.stopper.get_pre_stop() {
+            if let Err(err) = self.run_stop_command(&pre_stop, "pre-stop command") {
                 log::warn!(
-                    "unable to kill the app \"{}\" gracefully, {}",
+                    "unable to run pre-stop command of app \"{}\", {}",
                     self.name,
                     err.to_string()
                 );
-                self.kill();
             }
         }
     }
+
+    fn update_pre_stop(&mut self) {
+        if self.status == AppStatus::Stopping && self.stopper.update_pre_stop(&self.name) {
+            self.run_stop_step();
+        }
+    }
+
+    /*
+     * Performs the current step of the stop sequence.
+     * Failed steps are skipped, if there are no steps left the app is killed
+     */
+    fn run_stop_step(&mut self) {
+        while let Some(step) = self.stopper.get_step() {
+            let result = match step {
+                AppStopStep::Signal { signal, .. } => {
+                    self.get_signal_target().and_then(|target| {
+                        // On success (at least one signal was sent), zero is returned.  On
+                        // error, -1 is returned
+                        match unsafe { libc::kill(target, signal) } {
+                            0 => Ok(()),
+                            _ => Err(Error::last_os_error()),
+                        }
+                    })
+                }
+                AppStopStep::Command { command, .. } => {
+                    self.run_stop_command(&command, "stop command")
+                }
+            };
+
+            match result {
+                Ok(()) => {
+                    self.stopper.step_performed();
+                    return;
+                }
+                Err(err) => {
+                    log::warn!(
+                        "unable to stop the app \"{}\" gracefully (step {}), {}",
+                        self.name,
+                        self.stopper.get_step_number(),
+                        err.to_string()
+                    );
+                    self.stopper.skip_step();
+                }
+            }
+        }
+
+        self.stopper.escalate();
+        self.kill();
+    }
+
+    fn run_stop_command(&mut self, stop_command: &[String], kind: &str) -> Result<(), Error> {
+        let pid = self.get_pid().ok_or(Error::other("unable to get pid"))?;
+
+        let mut full_command: Vec<_> = stop_command.iter().map(|i| i.trim()).collect();
+        let executable = full_command.remove(0);
+        let args = full_command;
+
+        let mut command = Command::new(executable);
+
+        command
+            .envs(env::vars())
+            .env("MAESTRO_APP_PID", pid.to_string())
+            .args(args)
+            .uid(self.uid)
+            .stdin(Stdio::null())
+            .stdout(self.redirect_stdio(self.stdout.to_owned()))
+            .stderr(self.redirect_stdio(self.stderr.to_owned()));
+
+        let command_pid = self
+            .stopper
+            .spawn_command(detach_from_terminal(&mut command))?;
+
+        log::info!(
+            "{} of app \"{}\" is started, pid: {}",
+            kind,
+            self.name,
+            command_pid
+        );
+
+        Ok(())
+    }
+
+    /*
+     * The stop command is killed if the app has stopped before it completes
+     */
+    fn update_stop_command(&mut self) {
+        if self.status == AppStatus::Stopping {
+            self.stopper.update_command(&self.name);
+        } else {
+            self.stopper.kill_command();
+        }
+    }
+
+    /*
+     * Relays a signal received by maestro to the app if it is subscribed to it
+     */
+    pub fn forward_signal(&self, signal: i32) {
+        if self.status != AppStatus::Running || !self.forward_signals.contains(&signal) {
+            return;
+        }
+
+        let code = match self.get_signal_target() {
+            Ok(target) => unsafe { libc::kill(target, signal) },
+            Err(_) => -1,
+        };
+
+        if code != 0 {
+            log::warn!(
+                "unable to forward signal {} to the app \"{}\"",
+                signal,
+                self.name
+            );
+        } else {
+            log::info!("signal {} forwarded to the app \"{}\"", signal, self.name);
+        }
+    }
+}
+
+impl App {
+    /*
+     * Puts the app into the state as if it had run, for tests of the dependency logic
+     */
+    #[cfg(test)]
+    pub fn set_state(&mut self, status: AppStatus, ready: bool, exit_code: Option<i32>) {
+        self.status = status;
+        self.ready = ready;
+        self.exit_code = exit_code;
+    }
 }
 
 impl From<ConfigApp> for App {
@@ -331,22 +1109,346 @@ impl From<ConfigApp> for App {
         ConfigApp {
             name,
             command,
+            app_type,
+            timeout,
             stdout,
             stderr,
             signal,
             uid,
             ready,
+            liveness,
+            restart,
+            restart_backoff,
+            start_limit,
+            stop_timeout,
+            stop_command,
+            stop_sequence,
+            pre_stop,
+            stop_delay,
+            on_failure,
+            success_exit_codes,
+            schedule,
+            overlap,
+            process_group,
+            forward_signals,
             ..
         }: ConfigApp,
     ) -> Self {
-        Self::new(
+        /*
+         * A single signal or command is just a sequence of one step
+         */
+        let stop_sequence = match (stop_sequence, stop_command) {
+            (Some(steps), _) => steps.into_iter().map(AppStopStep::from).collect(),
+            (None, Some(command)) => vec![AppStopStep::Command {
+                command,
+                wait: None,
+            }],
+            (None, None) => vec![AppStopStep::Signal { signal, wait: None }],
+        };
+
+        log::info!("app \"{}\" created", name);
+
+        Self {
             name,
             command,
+            app_type: AppType::from(app_type),
+            timeout,
             uid,
-            AppReadinessProbe::from(ready),
-            signal,
+            ready: false,
+            readiness_probe: AppReadinessProbe::from(ready),
+            liveness_probe: liveness.map(AppLivenessProbe::from),
+            restart_policy: AppRestartPolicy::from(restart),
+            restart_backoff: AppRestartBackoff::from(restart_backoff),
+            start_limit: AppStartLimit::from(start_limit),
+            failure_policy: AppFailurePolicy::from(on_failure),
+            success_exit_codes,
+            schedule: schedule.map(AppSchedule::from),
+            overlap_policy: AppOverlapPolicy::from(overlap),
+            process_group,
+            forward_signals,
+
+            process: None,
+            stopper: AppStopper::new(stop_sequence, stop_timeout, pre_stop, stop_delay),
+            status: AppStatus::Init,
+            exit_code: None,
+            exit_signal: None,
+            restarts: 0,
+
+            started_at: None,
+            updated_at: get_now(),
+            ready_checked_at: None,
+            ready_check: None,
+            ready_at: None,
+            liveness_checked_at: None,
+            liveness_check: None,
+            liveness_failures: 0,
+            liveness_failed: false,
+            restarted_at: vec![],
+            restart_at: None,
+            stop_requested: false,
+            timed_out: false,
+            schedule_checked_at: None,
+            queued: false,
+
             stdout,
             stderr,
-        )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, thread, time::Duration};
+
+    use super::*;
+
+    fn create_app(config: &str) -> App {
+        let mut config_app: ConfigApp = serde_yaml::from_str(config).unwrap();
+
+        config_app.stop_timeout.get_or_insert(1000);
+
+        App::from(config_app)
+    }
+
+    fn wait_for_exit(app: &mut App) {
+        for _ in 0..200 {
+            app.update();
+
+            if app.has_exited() {
+                return;
+            }
+
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        panic!("app \"{}\" has not exited", app.get_name());
+    }
+
+    #[test]
+    fn failed_app_is_not_ready() {
+        let mut app = create_app(
+            r#"
+            name: db
+            command: ["sh", "-c", "sleep 0.1; exit 1"]
+            restart: always
+            start_limit:
+              burst: 1
+              interval: 10000
+            "#,
+        );
+
+        app.run();
+        app.update();
+
+        assert!(app.satisfies(AppDependencyCondition::ServiceReady));
+
+        wait_for_exit(&mut app);
+
+        assert_eq!(app.get_status(), AppStatus::Stopped);
+        assert!(!app.is_ready());
+        assert!(!app.satisfies(AppDependencyCondition::ServiceReady));
+
+        app.restart();
+        app.reset();
+        app.run();
+        app.update();
+        wait_for_exit(&mut app);
+
+        // the start limit is hit
+        app.restart();
+
+        assert_eq!(app.get_status(), AppStatus::Failed);
+        assert!(!app.is_ready());
+        assert!(!app.satisfies(AppDependencyCondition::ServiceReady));
+        assert!(!app.satisfies(AppDependencyCondition::ServiceStarted));
+    }
+
+    #[test]
+    fn proceeds_once_pre_stop_command_completes() {
+        let mut app = create_app(
+            r#"
+            name: app
+            command: ["sleep", "10"]
+            pre_stop: ["sleep", "0.3"]
+            stop_delay: 100
+            stop_timeout: 3000
+            "#,
+        );
+
+        app.run();
+        app.stop();
+
+        assert!(app.stopper.is_pre_stopping());
+        assert!(app
+            .get_next_deadline(get_now())
+            .is_some_and(|x| x <= get_now() + Duration::from_millis(POLL_PERIOD)));
+
+        // a single update after the command has completed is enough
+        thread::sleep(Duration::from_millis(500));
+        app.update();
+
+        assert!(!app.stopper.is_pre_stopping());
+
+        wait_for_exit(&mut app);
+
+        assert_eq!(app.exit_signal, Some(libc::SIGTERM));
+    }
+
+    #[test]
+    fn replaces_running_stop_command() {
+        let mut app = create_app(
+            r#"
+            name: app
+            command: ["sleep", "10"]
+            stop_sequence:
+              - command: ["sleep", "10"]
+                wait: 50
+              - command: ["sleep", "10"]
+                wait: 50
+              - signal: KILL
+            "#,
+        );
+
+        app.run();
+        app.stop();
+
+        let first = app.stopper.get_command_pid().unwrap();
+
+        thread::sleep(Duration::from_millis(100));
+        app.update();
+
+        let second = app.stopper.get_command_pid().unwrap();
+
+        assert_ne!(first, second);
+        assert!(!Path::new(&format!("/proc/{}", first)).exists());
+
+        app.force_kill();
+        app.update();
+        app.stopper.kill_command();
+
+        assert!(!Path::new(&format!("/proc/{}", second)).exists());
+    }
+
+    #[test]
+    fn restarts_only_on_policy() {
+        for (restart, command, expected) in [
+            ("never", "exit 1", false),
+            ("on-failure", "exit 0", false),
+            ("on-failure", "exit 1", true),
+            ("always", "exit 0", true),
+            ("unless-stopped", "exit 0", true),
+        ] {
+            let mut app = create_app(&format!(
+                r#"
+                name: worker
+                command: ["sh", "-c", "{}"]
+                restart: {}
+                "#,
+                command, restart
+            ));
+
+            assert!(!app.should_restart());
+
+            app.run();
+            wait_for_exit(&mut app);
+
+            assert_eq!(app.should_restart(), expected, "{} / {}", restart, command);
+        }
+    }
+
+    #[test]
+    fn increases_restart_delay_up_to_max() {
+        let mut app = create_app(
+            r#"
+            name: worker
+            command: ["worker"]
+            restart: always
+            restart_backoff:
+              initial_delay: 100
+              multiplier: 3
+              max_delay: 1000
+              jitter: 0
+            start_limit:
+              burst: 10
+              interval: 60000
+            "#,
+        );
+        let mut delays: Vec<u128> = vec![];
+
+        for _ in 0..5 {
+            app.set_state(AppStatus::Stopped, false, Some(1));
+            app.restart();
+
+            assert_eq!(app.get_status(), AppStatus::Backoff);
+
+            let restarted_at = *app.restarted_at.last().unwrap();
+
+            delays.push((app.restart_at.unwrap() - restarted_at).as_millis());
+        }
+
+        assert_eq!(delays, vec![100, 300, 900, 1000, 1000]);
+    }
+
+    #[test]
+    fn gives_up_after_start_limit_within_interval() {
+        let mut app = create_app(
+            r#"
+            name: worker
+            command: ["worker"]
+            restart: always
+            start_limit:
+              burst: 2
+              interval: 60000
+            "#,
+        );
+
+        for _ in 0..2 {
+            app.set_state(AppStatus::Stopped, false, Some(1));
+            app.restart();
+
+            assert_eq!(app.get_status(), AppStatus::Backoff);
+        }
+
+        // the first restart drops out of the interval, so there is room for one more
+        app.restarted_at[0] -= Duration::from_millis(60000);
+        app.set_state(AppStatus::Stopped, false, Some(1));
+        app.restart();
+
+        assert_eq!(app.get_status(), AppStatus::Backoff);
+        assert_eq!(app.restarted_at.len(), 2);
+
+        app.set_state(AppStatus::Stopped, false, Some(1));
+        app.restart();
+
+        assert_eq!(app.get_status(), AppStatus::Failed);
+        assert_eq!(app.restarts, 3);
+    }
+
+    #[test]
+    fn survives_clock_going_back() {
+        let mut app = create_app(
+            r#"
+            name: job
+            command: ["sleep", "1"]
+            type: oneshot
+            timeout: 100
+            ready:
+              delay: 100
+            "#,
+        );
+
+        app.run();
+
+        // as if the system clock had been set back by an hour
+        let hour = Duration::from_secs(3600);
+
+        app.started_at = app.started_at.map(|x| x + hour);
+        app.update();
+
+        assert_eq!(app.get_status(), AppStatus::Running);
+        assert!(!app.is_ready());
+
+        app.stop();
+        wait_for_exit(&mut app);
     }
 }
@@ -0,0 +1,33 @@
+use crate::config::ConfigDependency;
+
+use super::AppDependencyCondition;
+
+#[derive(Debug, Clone)]
+pub struct AppDependency {
+    pub name: String,
+    pub condition: AppDependencyCondition,
+
+    // An optional dependency does not block the app if it is missing or has failed
+    pub required: bool,
+}
+
+impl From<ConfigDependency> for AppDependency {
+    fn from(value: ConfigDependency) -> Self {
+        match value {
+            ConfigDependency::Name(name) => Self {
+                name,
+                condition: AppDependencyCondition::ServiceReady,
+                required: true,
+            },
+            ConfigDependency::Extended {
+                name,
+                condition,
+                required,
+            } => Self {
+                name,
+                condition: AppDependencyCondition::from(condition),
+                required,
+            },
+        }
+    }
+}
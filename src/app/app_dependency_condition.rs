@@ -0,0 +1,25 @@
+use crate::config::ConfigDependencyCondition;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AppDependencyCondition {
+    // The dependency is RUNNING (or READY)
+    ServiceStarted,
+
+    // The dependency is READY
+    ServiceReady,
+
+    // The dependency has exited with one of its success exit codes
+    CompletedSuccessfully,
+}
+
+impl From<ConfigDependencyCondition> for AppDependencyCondition {
+    fn from(value: ConfigDependencyCondition) -> Self {
+        match value {
+            ConfigDependencyCondition::ServiceStarted => AppDependencyCondition::ServiceStarted,
+            ConfigDependencyCondition::ServiceReady => AppDependencyCondition::ServiceReady,
+            ConfigDependencyCondition::CompletedSuccessfully => {
+                AppDependencyCondition::CompletedSuccessfully
+            }
+        }
+    }
+}
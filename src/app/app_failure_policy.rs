@@ -0,0 +1,28 @@
+use crate::config::ConfigFailurePolicy;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AppFailurePolicy {
+    // All apps are stopped and maestro exits
+    Shutdown,
+
+    // The failure is only logged, other apps keep running
+    Ignore,
+
+    // All apps that depend on the failed app (directly or not) are stopped
+    StopDependents,
+
+    // Dependents are stopped, then the failed app is restarted
+    // and dependents are started again once it is ready
+    Restart,
+}
+
+impl From<ConfigFailurePolicy> for AppFailurePolicy {
+    fn from(value: ConfigFailurePolicy) -> Self {
+        match value {
+            ConfigFailurePolicy::Shutdown => AppFailurePolicy::Shutdown,
+            ConfigFailurePolicy::Ignore => AppFailurePolicy::Ignore,
+            ConfigFailurePolicy::StopDependents => AppFailurePolicy::StopDependents,
+            ConfigFailurePolicy::Restart => AppFailurePolicy::Restart,
+        }
+    }
+}
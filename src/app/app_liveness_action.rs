@@ -0,0 +1,23 @@
+use crate::config::ConfigLivenessAction;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AppLivenessAction {
+    // The app is stopped and started again (the start limit is respected)
+    Restart,
+
+    // All apps are stopped and maestro exits
+    Shutdown,
+
+    // The failure is only logged
+    Log,
+}
+
+impl From<ConfigLivenessAction> for AppLivenessAction {
+    fn from(value: ConfigLivenessAction) -> Self {
+        match value {
+            ConfigLivenessAction::Restart => AppLivenessAction::Restart,
+            ConfigLivenessAction::Shutdown => AppLivenessAction::Shutdown,
+            ConfigLivenessAction::Log => AppLivenessAction::Log,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use crate::config::ConfigLivenessProbe;
+
+use super::{AppLivenessAction, AppReadinessProbe};
+
+#[derive(Debug)]
+pub struct AppLivenessProbe {
+    // Only `command` and `http` probes can be used
+    pub probe: AppReadinessProbe,
+
+    // Max duration (ms) of a single check
+    pub timeout: u32,
+
+    // How many checks in a row must fail for the app to be considered dead
+    pub failure_threshold: u32,
+
+    // Delay (ms) after the app has become ready before the first check
+    pub initial_delay: u32,
+
+    pub action: AppLivenessAction,
+}
+
+impl From<ConfigLivenessProbe> for AppLivenessProbe {
+    fn from(
+        ConfigLivenessProbe {
+            probe,
+            timeout,
+            failure_threshold,
+            initial_delay,
+            action,
+        }: ConfigLivenessProbe,
+    ) -> Self {
+        Self {
+            probe: AppReadinessProbe::from(probe),
+            timeout,
+            failure_threshold,
+            initial_delay,
+            action: AppLivenessAction::from(action),
+        }
+    }
+}
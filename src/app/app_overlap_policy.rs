@@ -0,0 +1,23 @@
+use crate::config::ConfigOverlapPolicy;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AppOverlapPolicy {
+    // The new run is skipped if the previous one is still running
+    Skip,
+
+    // The new run starts as soon as the previous one has finished
+    Queue,
+
+    // The previous run is stopped and then the new one starts
+    KillPrevious,
+}
+
+impl From<ConfigOverlapPolicy> for AppOverlapPolicy {
+    fn from(value: ConfigOverlapPolicy) -> Self {
+        match value {
+            ConfigOverlapPolicy::Skip => AppOverlapPolicy::Skip,
+            ConfigOverlapPolicy::Queue => AppOverlapPolicy::Queue,
+            ConfigOverlapPolicy::KillPrevious => AppOverlapPolicy::KillPrevious,
+        }
+    }
+}
@@ -12,11 +12,13 @@ pub enum AppReadinessProbe {
     Command {
         command: Vec<String>,
         period: u32,
+        timeout: u32,
     },
     Http {
         url: String,
         method: String,
         period: u32,
+        timeout: u32,
     },
 }
 
@@ -27,18 +29,26 @@ impl From<ConfigReadinessProbe> for AppReadinessProbe {
             ConfigReadinessProbe::ExitCode { exit_code } => {
                 AppReadinessProbe::ExitCode { exit_code }
             }
-            ConfigReadinessProbe::Command { command, period } => {
-                AppReadinessProbe::Command { command, period }
-            }
+            ConfigReadinessProbe::Command {
+                command,
+                period,
+                timeout,
+            } => AppReadinessProbe::Command {
+                command,
+                period,
+                timeout,
+            },
             ConfigReadinessProbe::Delay { delay } => AppReadinessProbe::Delay { delay },
             ConfigReadinessProbe::Http {
                 url,
                 method,
                 period,
+                timeout,
             } => AppReadinessProbe::Http {
                 url,
                 method,
                 period,
+                timeout,
             },
         }
     }
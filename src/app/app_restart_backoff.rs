@@ -0,0 +1,43 @@
+use crate::{config::ConfigRestartBackoff, utils::get_random};
+
+#[derive(Debug)]
+pub struct AppRestartBackoff {
+    initial_delay: u32,
+    multiplier: f64,
+    max_delay: u32,
+    jitter: f64,
+}
+
+impl AppRestartBackoff {
+    /*
+     * Returns the delay (ms) before the n-th (starting from 1) restart in a row:
+     * initial_delay * multiplier ^ (n - 1), but not more than max_delay,
+     * randomly spread by ±jitter
+     */
+    pub fn get_delay(&self, attempt: u32) -> u32 {
+        let exponent = attempt.saturating_sub(1) as i32;
+        let delay =
+            (self.initial_delay as f64 * self.multiplier.powi(exponent)).min(self.max_delay as f64);
+        let spread = delay * self.jitter * (get_random() * 2.0 - 1.0);
+
+        (delay + spread).max(0.0).round() as u32
+    }
+}
+
+impl From<ConfigRestartBackoff> for AppRestartBackoff {
+    fn from(
+        ConfigRestartBackoff {
+            initial_delay,
+            multiplier,
+            max_delay,
+            jitter,
+        }: ConfigRestartBackoff,
+    ) -> Self {
+        Self {
+            initial_delay,
+            multiplier,
+            max_delay,
+            jitter,
+        }
+    }
+}
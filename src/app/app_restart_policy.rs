@@ -0,0 +1,28 @@
+use crate::config::ConfigRestartPolicy;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AppRestartPolicy {
+    // The app is never restarted
+    Never,
+
+    // The app is restarted only if it has failed
+    OnFailure,
+
+    // The app is restarted regardless of the exit code
+    Always,
+
+    // Same as `Always`, but the app is not restarted
+    // if maestro itself has stopped it
+    UnlessStopped,
+}
+
+impl From<ConfigRestartPolicy> for AppRestartPolicy {
+    fn from(value: ConfigRestartPolicy) -> Self {
+        match value {
+            ConfigRestartPolicy::Never => AppRestartPolicy::Never,
+            ConfigRestartPolicy::OnFailure => AppRestartPolicy::OnFailure,
+            ConfigRestartPolicy::Always => AppRestartPolicy::Always,
+            ConfigRestartPolicy::UnlessStopped => AppRestartPolicy::UnlessStopped,
+        }
+    }
+}
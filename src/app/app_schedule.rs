@@ -0,0 +1,16 @@
+use crate::{config::ConfigSchedule, cron::CronExpression};
+
+#[derive(Debug)]
+pub enum AppSchedule {
+    Interval(u32),
+    Cron(CronExpression),
+}
+
+impl From<ConfigSchedule> for AppSchedule {
+    fn from(value: ConfigSchedule) -> Self {
+        match value {
+            ConfigSchedule::Interval(interval) => AppSchedule::Interval(interval),
+            ConfigSchedule::Cron(expression) => AppSchedule::Cron(expression),
+        }
+    }
+}
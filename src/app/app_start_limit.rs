@@ -0,0 +1,16 @@
+use crate::config::ConfigStartLimit;
+
+#[derive(Debug)]
+pub struct AppStartLimit {
+    // How many restarts are allowed within the interval
+    pub burst: u32,
+
+    // The interval (ms) in which restarts are counted
+    pub interval: u32,
+}
+
+impl From<ConfigStartLimit> for AppStartLimit {
+    fn from(ConfigStartLimit { burst, interval }: ConfigStartLimit) -> Self {
+        Self { burst, interval }
+    }
+}
@@ -14,6 +14,25 @@ pub enum AppStatus {
     // The process has stopped. Having a process to stop is mandatory.
     // There should be no transition Init -> Stopped
     Stopped,
+
+    // The app has stopped and is waiting to be restarted
+    Backoff,
+
+    // The app has been restarted too many times and will not be started again
+    Failed,
+}
+
+impl AppStatus {
+    /*
+     * There is no process attached to the app
+     * and there will be none unless maestro decides to (re)start it
+     */
+    pub fn is_inactive(&self) -> bool {
+        matches!(
+            self,
+            AppStatus::Init | AppStatus::Stopped | AppStatus::Failed
+        )
+    }
 }
 
 impl fmt::Display for AppStatus {
@@ -23,6 +42,8 @@ impl fmt::Display for AppStatus {
             AppStatus::Running => write!(f, "RUNNING"),
             AppStatus::Stopping => write!(f, "STOPPING"),
             AppStatus::Stopped => write!(f, "STOPPED"),
+            AppStatus::Backoff => write!(f, "BACKOFF"),
+            AppStatus::Failed => write!(f, "FAILED"),
         }
     }
 }
@@ -0,0 +1,35 @@
+use crate::config::ConfigStopStep;
+
+/*
+ * A single step of the graceful stop.
+ * After the step is performed, the app is given `wait` ms (or `stop_timeout`) to exit
+ */
+#[derive(Debug, Clone)]
+pub enum AppStopStep {
+    Signal {
+        signal: i32,
+        wait: Option<u32>,
+    },
+    Command {
+        command: Vec<String>,
+        wait: Option<u32>,
+    },
+}
+
+impl AppStopStep {
+    pub fn get_wait(&self) -> Option<u32> {
+        match self {
+            AppStopStep::Signal { wait, .. } => *wait,
+            AppStopStep::Command { wait, .. } => *wait,
+        }
+    }
+}
+
+impl From<ConfigStopStep> for AppStopStep {
+    fn from(value: ConfigStopStep) -> Self {
+        match value {
+            ConfigStopStep::Signal { signal, wait } => AppStopStep::Signal { signal, wait },
+            ConfigStopStep::Command { command, wait } => AppStopStep::Command { command, wait },
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    io::Error,
+    process::{Child, Command},
+    time::Duration,
+};
+
+use crate::{reaper, utils::get_now};
+
+use super::AppStopStep;
+
+/*
+ * What the app has to do when the current stop step is over
+ */
+pub enum AppStopAction {
+    RunStep,
+    Kill,
+}
+
+/*
+ * The graceful stop of an app: the pre-stop hook, the steps of the stop sequence
+ * with their timeouts and the stop command in progress. Sending signals and killing
+ * the app itself is up to the app
+ */
+#[derive(Debug)]
+pub struct AppStopper {
+    sequence: Vec<AppStopStep>,
+    timeout: Option<u32>,
+    pre_stop: Option<Vec<String>>,
+    delay: u32,
+
+    command: Option<Child>,
+
+    // Reset once the app is killed, there is nothing to wait for then
+    started_at: Option<Duration>,
+
+    step: usize,
+    pre_stopping: bool,
+}
+
+impl AppStopper {
+    pub fn new(
+        sequence: Vec<AppStopStep>,
+        timeout: Option<u32>,
+        pre_stop: Option<Vec<String>>,
+        delay: u32,
+    ) -> Self {
+        Self {
+            sequence,
+            timeout,
+            pre_stop,
+            delay,
+            command: None,
+            started_at: None,
+            step: 0,
+            pre_stopping: false,
+        }
+    }
+
+    /*
+     * Starts the stop from the pre-stop hook (if any) or the first step.
+     * Without the sequence the app is only given the stop timeout and then killed
+     */
+    pub fn start(&mut self, skip_sequence: bool) {
+        self.started_at = Some(get_now());
+
+        if skip_sequence {
+            self.step = self.sequence.len();
+            self.pre_stopping = false;
+
+            return;
+        }
+
+        self.step = 0;
+        self.pre_stopping = self.pre_stop.is_some() || self.delay > 0;
+    }
+
+    pub fn is_pre_stopping(&self) -> bool {
+        self.pre_stopping
+    }
+
+    pub fn has_command(&self) -> bool {
+        self.command.is_some()
+    }
+
+    pub fn get_pre_stop(&self) -> Option<Vec<String>> {
+        self.pre_stop.to_owned()
+    }
+
+    /*
+     * `true` until the app is killed
+     */
+    pub fn is_waiting(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    pub fn get_step(&self) -> Option<AppStopStep> {
+        self.sequence.get(self.step).cloned()
+    }
+
+    // Starts from 1, for messages
+    pub fn get_step_number(&self) -> usize {
+        self.step + 1
+    }
+
+    /*
+     * The current step has been performed, the app is given its timeout to exit.
+     * The timeout of the first step counts from the beginning of the stop,
+     * so it includes the pre-stop hook
+     */
+    pub fn step_performed(&mut self) {
+        if self.step > 0 {
+            self.started_at = Some(get_now());
+        }
+    }
+
+    pub fn skip_step(&mut self) {
+        self.step += 1;
+    }
+
+    pub fn escalate(&mut self) {
+        self.started_at = None;
+    }
+
+    fn get_timeout(&self) -> Option<u32> {
+        self.sequence
+            .get(self.step)
+            .and_then(|step| step.get_wait())
+            .or(self.timeout)
+    }
+
+    pub fn get_deadlines(&self) -> Vec<Duration> {
+        let ms = |x: u32| Duration::from_millis(x as u64);
+        let mut deadlines: Vec<Duration> = vec![];
+
+        if let Some(started) = self.started_at {
+            deadlines.extend(self.get_timeout().map(|x| started + ms(x)));
+
+            if self.pre_stopping {
+                deadlines.push(started + ms(self.delay));
+            }
+        }
+
+        deadlines
+    }
+
+    /*
+     * The pre-stop hook is over when both the command has completed and the delay has passed,
+     * or when the timeout of the first step has expired. Returns `true` if it is over
+     */
+    pub fn update_pre_stop(&mut self, name: &str) -> bool {
+        let (true, Some(started)) = (self.pre_stopping, self.started_at) else {
+            return false;
+        };
+
+        let ms = |x: u32| Duration::from_millis(x as u64);
+        let elapsed = get_now().saturating_sub(started);
+        let timed_out = self.get_timeout().is_some_and(|x| elapsed >= ms(x));
+
+        if !timed_out && (elapsed < ms(self.delay) || self.command.is_some()) {
+            return false;
+        }
+
+        if self.command.is_some() {
+            log::warn!(
+                "pre-stop command of app \"{}\" has not completed in time, killing it",
+                name
+            );
+            self.kill_command();
+        }
+
+        self.pre_stopping = false;
+
+        true
+    }
+
+    /*
+     * Proceeds to the next step once the timeout of the current one has expired,
+     * the app is killed after the last one
+     */
+    pub fn update_timeout(&mut self, name: &str) -> Option<AppStopAction> {
+        if self.pre_stopping {
+            return None;
+        }
+
+        let started = self.started_at?;
+        let timeout = self.get_timeout()?;
+
+        if get_now().saturating_sub(started) < Duration::from_millis(timeout as u64) {
+            return None;
+        }
+
+        self.step += 1;
+
+        if self.step < self.sequence.len() {
+            log::warn!(
+                "app \"{}\" has not stopped within {} ms, proceeding to step {} of the stop sequence",
+                name,
+                timeout,
+                self.get_step_number()
+            );
+
+            return Some(AppStopAction::RunStep);
+        }
+
+        log::warn!(
+            "app \"{}\" has not stopped within {} ms, escalating to SIGKILL",
+            name,
+            timeout
+        );
+
+        /*
+         * The escalation happens only once,
+         * then we are waiting for the process to exit
+         */
+        self.escalate();
+
+        Some(AppStopAction::Kill)
+    }
+
+    /*
+     * Starts the stop or pre-stop command, the previous one
+     * (e.g. of the previous stop step) is not needed anymore
+     */
+    pub fn spawn_command(&mut self, command: &mut Command) -> Result<u32, Error> {
+        self.kill_command();
+
+        let child = reaper::spawn(command)?;
+        let pid = child.id();
+
+        self.command = Some(child);
+
+        Ok(pid)
+    }
+
+    /*
+     * Collects the exit status of the stop command
+     */
+    pub fn update_command(&mut self, name: &str) {
+        let Some(process) = &mut self.command else {
+            return;
+        };
+
+        match process.try_wait() {
+            Ok(None) => return,
+            Ok(Some(exit_status)) if !exit_status.success() => {
+                log::warn!(
+                    "stop command of app \"{}\" has failed, {}",
+                    name,
+                    exit_status
+                );
+            }
+            Ok(Some(_)) => {}
+            Err(err) => {
+                log::warn!(
+                    "unable to check the stop command of app \"{}\", {}",
+                    name,
+                    err.to_string()
+                );
+            }
+        }
+
+        reaper::release(process.id());
+        self.command = None;
+    }
+
+    pub fn kill_command(&mut self) {
+        if let Some(mut process) = self.command.take() {
+            process.kill().ok();
+            process.wait().ok();
+            reaper::release(process.id());
+        }
+    }
+
+    #[cfg(test)]
+    pub fn get_command_pid(&self) -> Option<u32> {
+        self.command.as_ref().map(|process| process.id())
+    }
+}
@@ -0,0 +1,20 @@
+use crate::config::ConfigAppType;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AppType {
+    // A long-running app, it is stopped by maestro during shutdown
+    Service,
+
+    // A task that is expected to exit on its own (migrations, cache warmers, etc.),
+    // it is considered READY once it has exited successfully
+    Oneshot,
+}
+
+impl From<ConfigAppType> for AppType {
+    fn from(value: ConfigAppType) -> Self {
+        match value {
+            ConfigAppType::Service => AppType::Service,
+            ConfigAppType::Oneshot => AppType::Oneshot,
+        }
+    }
+}
@@ -1,13 +1,13 @@
 use std::{
     cell::RefCell,
-    collections::{hash_map::Values, HashMap},
+    collections::{hash_map::Values, HashMap, HashSet},
 };
 
-use super::App;
+use super::{App, AppDependency, AppDependencyCondition, AppRestartPolicy, AppType};
 
 pub struct AppsMap {
     map: HashMap<String, RefCell<App>>,
-    run_after: HashMap<String, Vec<String>>,
+    run_after: HashMap<String, Vec<AppDependency>>,
     run_before: HashMap<String, Vec<String>>,
 }
 
@@ -20,11 +20,14 @@ impl AppsMap {
         }
     }
 
-    pub fn add(&mut self, app: App, deps: Vec<String>) {
+    pub fn add(&mut self, app: App, deps: Vec<AppDependency>) {
         self.run_after.insert(app.get_name(), deps.to_owned());
 
         for dep in deps {
-            self.run_before.entry(dep).or_default().push(app.get_name());
+            self.run_before
+                .entry(dep.name)
+                .or_default()
+                .push(app.get_name());
         }
 
         self.map.insert(app.get_name(), RefCell::new(app));
@@ -48,17 +51,403 @@ impl AppsMap {
         self.map.values()
     }
 
-    pub fn get_dependencies_for(&self, app_name: &String) -> Vec<String> {
+    pub fn get_dependencies_for(&self, app_name: &String) -> Vec<AppDependency> {
         match self.run_after.get(app_name) {
             None => Vec::new(),
             Some(value) => value.to_owned(),
         }
     }
 
+    /*
+     * Returns the edge between the app and its dependency
+     */
+    fn get_dependency(&self, app_name: &String, dep_name: &String) -> Option<AppDependency> {
+        self.get_dependencies_for(app_name)
+            .into_iter()
+            .find(|dep| dep.name == *dep_name)
+    }
+
+    /*
+     * The conditions of all dependencies of the app are met, so the app can be started.
+     * Optional dependencies that are missing or have failed are skipped,
+     * a failed required dependency is never met (see `get_failed_dependency`)
+     */
+    pub fn dependencies_ready(&self, app_name: &String) -> bool {
+        self.get_dependencies_for(app_name)
+            .iter()
+            .all(|dep| match self.get(&dep.name) {
+                None => !dep.required,
+                Some(app) => {
+                    let app = app.borrow();
+
+                    match app.has_failed() {
+                        true => !dep.required,
+                        false => app.satisfies(dep.condition),
+                    }
+                }
+            })
+    }
+
+    /*
+     * Returns a required dependency of the app that has failed (or stopped with an error)
+     * and will not be started again, the app can not be started until it is restarted
+     */
+    pub fn get_failed_dependency(&self, app_name: &String) -> Option<String> {
+        self.get_dependencies_for(app_name)
+            .into_iter()
+            .filter(|dep| dep.required)
+            .find(|dep| {
+                self.get(&dep.name)
+                    .is_some_and(|app| app.borrow().has_failed())
+            })
+            .map(|dep| dep.name)
+    }
+
+    /*
+     * Finds out why the app can never be started, if so.
+     * Returns the dependency chain (e.g. ["a", "b", "c"]) and the reason
+     */
+    pub fn find_unsatisfiable_chain(&self, app_name: &String) -> Option<(Vec<String>, String)> {
+        for dep in self.get_dependencies_for(app_name) {
+            if !dep.required {
+                continue;
+            }
+
+            let Some(dep_app) = self.get(&dep.name) else {
+                continue;
+            };
+
+            /*
+             * Such an app is started again as soon as it exits, even successfully
+             */
+            let always_restarted = matches!(
+                dep_app.borrow().get_restart_policy(),
+                AppRestartPolicy::Always | AppRestartPolicy::UnlessStopped
+            );
+
+            if dep.condition == AppDependencyCondition::CompletedSuccessfully && always_restarted {
+                return Some((
+                    vec![app_name.to_owned(), dep.name.to_owned()],
+                    format!(
+                        "\"{}\" is always restarted, so it never completes",
+                        dep.name
+                    ),
+                ));
+            }
+
+            if let Some((mut chain, reason)) = self.find_unsatisfiable_chain(&dep.name) {
+                chain.insert(0, app_name.to_owned());
+
+                return Some((chain, reason));
+            }
+        }
+
+        None
+    }
+
+    /*
+     * All dependents of the app are not running anymore, so the app can be stopped.
+     * Dependents that only needed the app to complete do not need it to keep running,
+     * and running oneshot dependents do not hold it either (they may never complete)
+     */
+    pub fn dependents_stopped(&self, app_name: &String) -> bool {
+        self.get_dependents_for(app_name).iter().all(|dep| {
+            self.get_dependency(dep, app_name)
+                .is_some_and(|x| x.condition == AppDependencyCondition::CompletedSuccessfully)
+                || self.get(dep).is_some_and(|app| {
+                    let app = app.borrow();
+
+                    app.get_type() == AppType::Oneshot || app.get_status().is_inactive()
+                })
+        })
+    }
+
     pub fn get_dependents_for(&self, app_name: &String) -> Vec<String> {
         match self.run_before.get(app_name) {
             None => Vec::new(),
             Some(value) => value.to_owned(),
         }
     }
+
+    /*
+     * Returns all apps that require the app, directly or through other apps.
+     * Apps that depend on it optionally are not affected by its failure
+     */
+    pub fn get_all_dependents_for(&self, app_name: &String) -> Vec<String> {
+        let required_dependents = |name: &String| -> Vec<String> {
+            self.get_dependents_for(name)
+                .into_iter()
+                .filter(|dep| self.get_dependency(dep, name).is_some_and(|x| x.required))
+                .collect()
+        };
+
+        let mut visited: HashSet<String> = HashSet::new();
+        let mut queue = required_dependents(app_name);
+
+        while let Some(dependent) = queue.pop() {
+            if visited.insert(dependent.to_owned()) {
+                queue.extend(required_dependents(&dependent));
+            }
+        }
+
+        visited.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::ConfigApp;
+
+    use super::{super::AppStatus, *};
+
+    fn create_map(config: &str) -> AppsMap {
+        let apps: Vec<ConfigApp> = serde_yaml::from_str(config).unwrap();
+        let mut apps_map = AppsMap::new();
+
+        for app in apps {
+            let deps = app
+                .depends_on
+                .iter()
+                .cloned()
+                .map(AppDependency::from)
+                .collect();
+
+            apps_map.add(App::from(app), deps);
+        }
+
+        apps_map
+    }
+
+    fn set_state(
+        apps_map: &AppsMap,
+        name: &str,
+        status: AppStatus,
+        ready: bool,
+        exit_code: Option<i32>,
+    ) {
+        apps_map
+            .get(&name.to_string())
+            .unwrap()
+            .borrow_mut()
+            .set_state(status, ready, exit_code);
+    }
+
+    const DEPENDENCIES: &str = r#"
+        - name: db
+          command: ["db"]
+        - name: ready
+          command: ["app"]
+          depends_on: [db]
+        - name: started
+          command: ["app"]
+          depends_on: [{name: db, condition: service_started}]
+        - name: optional
+          command: ["app"]
+          depends_on: [{name: db, required: false}]
+        "#;
+
+    #[test]
+    fn waits_for_dependency_to_start_and_become_ready() {
+        let apps_map = create_map(DEPENDENCIES);
+        let ready = |name: &str| apps_map.dependencies_ready(&name.to_string());
+
+        assert!(!ready("ready") && !ready("started") && !ready("optional"));
+
+        set_state(&apps_map, "db", AppStatus::Running, false, None);
+
+        assert!(!ready("ready") && ready("started") && !ready("optional"));
+
+        set_state(&apps_map, "db", AppStatus::Running, true, None);
+
+        assert!(ready("ready") && ready("started") && ready("optional"));
+    }
+
+    #[test]
+    fn stopped_dependency_is_neither_started_nor_ready() {
+        let apps_map = create_map(DEPENDENCIES);
+        let ready = |name: &str| apps_map.dependencies_ready(&name.to_string());
+
+        // a stale ready flag does not matter, only a running app is started or ready
+        for (status, is_ready) in [
+            (AppStatus::Stopping, true),
+            (AppStatus::Stopped, false),
+            (AppStatus::Backoff, true),
+        ] {
+            set_state(&apps_map, "db", status, is_ready, Some(0));
+
+            assert!(!ready("ready") && !ready("started") && !ready("optional"));
+            assert_eq!(apps_map.get_failed_dependency(&"ready".to_string()), None);
+        }
+    }
+
+    #[test]
+    fn failed_dependency_blocks_only_required_dependents() {
+        let apps_map = create_map(DEPENDENCIES);
+        let ready = |name: &str| apps_map.dependencies_ready(&name.to_string());
+        let failed = |name: &str| apps_map.get_failed_dependency(&name.to_string());
+
+        for (status, exit_code) in [(AppStatus::Stopped, Some(1)), (AppStatus::Failed, None)] {
+            set_state(&apps_map, "db", status, true, exit_code);
+
+            assert!(!ready("ready") && !ready("started"));
+            assert_eq!(failed("ready").as_deref(), Some("db"));
+            assert_eq!(failed("started").as_deref(), Some("db"));
+
+            assert!(ready("optional"));
+            assert_eq!(failed("optional"), None);
+        }
+    }
+
+    #[test]
+    fn completed_oneshot_satisfies_dependents() {
+        let apps_map = create_map(
+            r#"
+            - name: migrate
+              command: ["migrate"]
+              type: oneshot
+            - name: ready
+              command: ["app"]
+              depends_on: [migrate]
+            - name: completed
+              command: ["app"]
+              depends_on: [{name: migrate, condition: completed_successfully}]
+            "#,
+        );
+        let ready = |name: &str| apps_map.dependencies_ready(&name.to_string());
+
+        set_state(&apps_map, "migrate", AppStatus::Running, false, None);
+
+        assert!(!ready("ready") && !ready("completed"));
+
+        set_state(&apps_map, "migrate", AppStatus::Stopped, true, Some(0));
+
+        assert!(ready("ready") && ready("completed"));
+    }
+
+    #[test]
+    fn missing_dependency_blocks_only_required_dependents() {
+        let apps_map = create_map(
+            r#"
+            - name: required
+              command: ["app"]
+              depends_on: [nope]
+            - name: optional
+              command: ["app"]
+              depends_on: [{name: nope, required: false}]
+            "#,
+        );
+
+        assert!(!apps_map.dependencies_ready(&"required".to_string()));
+        assert!(apps_map.dependencies_ready(&"optional".to_string()));
+    }
+
+    #[test]
+    fn finds_dependency_that_never_completes() {
+        let apps_map = create_map(
+            r#"
+            - name: server
+              command: ["server"]
+              restart: unless-stopped
+            - name: migrate
+              command: ["migrate"]
+              restart: on-failure
+            - name: setup
+              command: ["setup"]
+              depends_on:
+                - {name: migrate, condition: completed_successfully}
+                - {name: server, condition: completed_successfully, required: false}
+            - name: worker
+              command: ["worker"]
+              depends_on: [{name: server, condition: completed_successfully}]
+            - name: web
+              command: ["web"]
+              depends_on: [setup, worker]
+            "#,
+        );
+        let find = |name: &str| apps_map.find_unsatisfiable_chain(&name.to_string());
+
+        // an optional dependency does not hold the app back
+        assert_eq!(find("setup"), None);
+        assert_eq!(
+            find("worker"),
+            Some((
+                vec!["worker".into(), "server".into()],
+                "\"server\" is always restarted, so it never completes".into()
+            ))
+        );
+        assert_eq!(
+            find("web"),
+            Some((
+                vec!["web".into(), "worker".into(), "server".into()],
+                "\"server\" is always restarted, so it never completes".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn gets_required_dependents_transitively() {
+        let apps_map = create_map(
+            r#"
+            - name: db
+              command: ["db"]
+            - name: api
+              command: ["api"]
+              depends_on: [db]
+            - name: web
+              command: ["web"]
+              depends_on: [api, db]
+            - name: metrics
+              command: ["metrics"]
+              depends_on: [{name: db, required: false}]
+            - name: dashboard
+              command: ["dashboard"]
+              depends_on: [metrics]
+            "#,
+        );
+        let dependents = |name: &str| {
+            let mut result = apps_map.get_all_dependents_for(&name.to_string());
+            result.sort();
+
+            result
+        };
+
+        assert_eq!(dependents("db"), vec!["api", "web"]);
+        assert_eq!(dependents("metrics"), vec!["dashboard"]);
+        assert!(dependents("web").is_empty());
+    }
+
+    #[test]
+    fn waits_for_running_dependents_to_stop() {
+        let apps_map = create_map(
+            r#"
+            - name: db
+              command: ["db"]
+            - name: api
+              command: ["api"]
+              depends_on: [db]
+            - name: migrate
+              command: ["migrate"]
+              type: oneshot
+              depends_on: [db]
+            - name: backup
+              command: ["backup"]
+              depends_on: [{name: db, condition: completed_successfully}]
+            "#,
+        );
+        let stopped = || apps_map.dependents_stopped(&"db".to_string());
+
+        for name in ["api", "migrate", "backup"] {
+            set_state(&apps_map, name, AppStatus::Running, true, None);
+        }
+
+        assert!(!stopped());
+
+        set_state(&apps_map, "api", AppStatus::Stopping, false, None);
+
+        assert!(!stopped());
+
+        set_state(&apps_map, "api", AppStatus::Failed, false, None);
+
+        assert!(stopped());
+    }
 }
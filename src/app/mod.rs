@@ -1,9 +1,36 @@
+#[allow(clippy::module_inception)]
 mod app;
+mod app_dependency;
+mod app_dependency_condition;
+mod app_failure_policy;
+mod app_liveness_action;
+mod app_liveness_probe;
+mod app_overlap_policy;
 mod app_readiness_probe;
+mod app_restart_backoff;
+mod app_restart_policy;
+mod app_schedule;
+mod app_start_limit;
 mod app_status;
+mod app_stop_step;
+mod app_stopper;
+mod app_type;
 mod apps_map;
 
 pub use app::App;
+pub use app_dependency::AppDependency;
+pub use app_dependency_condition::AppDependencyCondition;
+pub use app_failure_policy::AppFailurePolicy;
+pub use app_liveness_action::AppLivenessAction;
+pub use app_liveness_probe::AppLivenessProbe;
+pub use app_overlap_policy::AppOverlapPolicy;
 pub use app_readiness_probe::AppReadinessProbe;
+pub use app_restart_backoff::AppRestartBackoff;
+pub use app_restart_policy::AppRestartPolicy;
+pub use app_schedule::AppSchedule;
+pub use app_start_limit::AppStartLimit;
 pub use app_status::AppStatus;
+pub use app_stop_step::AppStopStep;
+pub use app_stopper::{AppStopAction, AppStopper};
+pub use app_type::AppType;
 pub use apps_map::AppsMap;
@@ -0,0 +1,154 @@
+use std::{
+    env,
+    ffi::CString,
+    os::unix::{ffi::OsStrExt, fs::PermissionsExt},
+    path::Path,
+};
+
+use crate::{
+    config::{Config, ConfigError, ConfigProblem},
+    utils::normalize_path,
+};
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+/*
+ * Executables without a slash are searched on PATH, the same way as the apps are started
+ */
+fn check_executable(executable: &str) -> Result<(), String> {
+    if executable.contains('/') {
+        if !is_executable(Path::new(executable)) {
+            return Err(format!(
+                "executable \"{}\" does not exist or is not executable",
+                executable
+            ));
+        }
+
+        return Ok(());
+    }
+
+    let found = env::var_os("PATH").is_some_and(|paths| {
+        env::split_paths(&paths).any(|dir| is_executable(&dir.join(executable)))
+    });
+
+    if !found {
+        return Err(format!(
+            "executable \"{}\" is not found on PATH",
+            executable
+        ));
+    }
+
+    Ok(())
+}
+
+fn is_writable(path: &Path) -> bool {
+    let c_path = match CString::new(path.as_os_str().as_bytes()) {
+        Ok(value) => value,
+        Err(_) => return false,
+    };
+
+    unsafe { libc::access(c_path.as_ptr(), libc::W_OK) == 0 }
+}
+
+/*
+ * Missing directories are created when the file is opened,
+ * so the nearest existing ancestor must be a writable directory
+ */
+fn check_writable(file_path: &str) -> Result<(), String> {
+    let file_path = normalize_path(file_path.to_owned());
+    let path = Path::new(&file_path);
+
+    if path.is_dir() {
+        return Err(format!("\"{}\" is a directory", file_path));
+    }
+
+    if path.exists() {
+        if !is_writable(path) {
+            return Err(format!("\"{}\" is not writable", file_path));
+        }
+
+        return Ok(());
+    }
+
+    let ancestor = path.ancestors().skip(1).find(|dir| dir.exists());
+
+    match ancestor {
+        Some(dir) if dir.is_dir() && is_writable(dir) => Ok(()),
+        Some(dir) => Err(format!(
+            "\"{}\" can not be created, \"{}\" is not a writable directory",
+            file_path,
+            dir.display()
+        )),
+        None => Err(format!("\"{}\" can not be created", file_path)),
+    }
+}
+
+fn check_config(config: &Config) -> Vec<ConfigProblem> {
+    let mut problems: Vec<ConfigProblem> = vec![];
+
+    if let Some(pid) = &config.pid {
+        if let Err(message) = check_writable(pid) {
+            problems.push(ConfigProblem {
+                field: Some("pid".to_string()),
+                ..ConfigProblem::new(message)
+            });
+        }
+    }
+
+    for app in config.apps.iter() {
+        // an empty command is reported by the validation
+        if let Some(Err(message)) = app.command.first().map(|x| check_executable(x.trim())) {
+            problems.push(ConfigProblem::for_app(app, "command", message));
+        }
+
+        for (field, value) in [("stdout", &app.stdout), ("stderr", &app.stderr)] {
+            let file_path = match value {
+                Some(value) if value != "inherit" => value,
+                _ => continue,
+            };
+
+            if let Err(message) = check_writable(file_path) {
+                problems.push(ConfigProblem::for_app(app, field, message));
+            }
+        }
+    }
+
+    problems
+}
+
+/*
+ * Loads and validates the config, checks that the apps can be started,
+ * but does not spawn anything. Returns the exit code
+ */
+pub fn run(config_path: Option<String>) -> i32 {
+    let mut config = match Config::new(config_path) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}", error);
+            return 1;
+        }
+    };
+
+    let mut problems = config.find_problems();
+
+    problems.extend(check_config(&config));
+
+    if !problems.is_empty() {
+        eprintln!(
+            "{}",
+            ConfigError {
+                path: config.path,
+                problems,
+            }
+        );
+
+        return 1;
+    }
+
+    println!("config {} is valid", config.path.unwrap_or_default());
+
+    0
+}
@@ -0,0 +1,192 @@
+use std::env;
+
+const USAGE: &str = "Usage: maestro [OPTIONS] [COMMAND]
+
+Commands:
+  check, validate          Validate the config and exit without starting apps
+
+Options:
+  -c, --config <PATH>      Path to the config file, \"-\" reads it from stdin [env: MAESTRO_CONFIG]
+  -l, --log-level <LEVEL>  Override log_level of the config (debug, info, warn or error)
+  -V, --version            Print version
+  -h, --help               Print help";
+
+#[derive(Debug, PartialEq)]
+pub enum CliCommand {
+    Run,
+    Check,
+}
+
+#[derive(Debug)]
+pub struct Cli {
+    pub command: CliCommand,
+
+    // None means the default locations
+    pub config: Option<String>,
+
+    pub log_level: Option<String>,
+}
+
+fn print_usage_and_exit(error: String) -> ! {
+    eprintln!("{}\n\n{}", error, USAGE);
+    std::process::exit(2);
+}
+
+impl Cli {
+    /*
+     * Parses the arguments of the current process, prints help or version and exits if requested
+     */
+    pub fn parse() -> Self {
+        match Self::parse_from(env::args().skip(1)) {
+            Ok(cli) => cli,
+            Err(error) => print_usage_and_exit(error),
+        }
+    }
+
+    fn parse_from(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut command: Option<CliCommand> = None;
+        let mut config: Option<String> = None;
+        let mut log_level: Option<String> = None;
+
+        while let Some(arg) = args.next() {
+            /*
+             * Both "--option value" and "--option=value" are supported
+             */
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name, Some(value.to_owned())),
+                _ => (arg.as_str(), None),
+            };
+
+            let mut value = || {
+                inline_value
+                    .to_owned()
+                    .or_else(|| args.next())
+                    .ok_or(format!("option {} requires a value", name))
+            };
+
+            match name {
+                "-c" | "--config" => config = Some(value()?),
+                "-l" | "--log-level" => log_level = Some(value()?),
+                "-V" | "--version" => {
+                    println!("maestro {}", env!("CARGO_PKG_VERSION"));
+                    std::process::exit(0);
+                }
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    std::process::exit(0);
+                }
+                "check" | "validate" if command.is_none() => command = Some(CliCommand::Check),
+                _ if name.starts_with('-') && name != "-" => {
+                    return Err(format!("unknown option {}", name))
+                }
+                _ => return Err(format!("unexpected argument \"{}\"", arg)),
+            }
+        }
+
+        Ok(Self {
+            command: command.unwrap_or(CliCommand::Run),
+            config: config.or(env::var("MAESTRO_CONFIG").ok().filter(|x| !x.is_empty())),
+            log_level,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, String> {
+        Cli::parse_from(args.iter().map(|x| x.to_string()))
+    }
+
+    #[test]
+    fn runs_by_default() {
+        let cli = parse(&[]).unwrap();
+
+        assert_eq!(cli.command, CliCommand::Run);
+        assert_eq!(cli.log_level, None);
+    }
+
+    #[test]
+    fn parses_check_command_and_alias() {
+        assert_eq!(parse(&["check"]).unwrap().command, CliCommand::Check);
+        assert_eq!(parse(&["validate"]).unwrap().command, CliCommand::Check);
+    }
+
+    #[test]
+    fn parses_options_with_separate_and_inline_values() {
+        let cli = parse(&["-c", "a.yml", "--log-level", "debug", "check"]).unwrap();
+
+        assert_eq!(cli.command, CliCommand::Check);
+        assert_eq!(cli.config.as_deref(), Some("a.yml"));
+        assert_eq!(cli.log_level.as_deref(), Some("debug"));
+
+        let cli = parse(&["--config=b.yml", "-l", "warn"]).unwrap();
+
+        assert_eq!(cli.config.as_deref(), Some("b.yml"));
+        assert_eq!(cli.log_level.as_deref(), Some("warn"));
+    }
+
+    #[test]
+    fn keeps_equals_sign_in_values() {
+        let cli = parse(&["--config=dir=1/a.yml"]).unwrap();
+
+        assert_eq!(cli.config.as_deref(), Some("dir=1/a.yml"));
+
+        let cli = parse(&["-c", "x=y.yml"]).unwrap();
+
+        assert_eq!(cli.config.as_deref(), Some("x=y.yml"));
+    }
+
+    #[test]
+    fn accepts_stdin_as_config() {
+        let cli = parse(&["--config", "-"]).unwrap();
+
+        assert_eq!(cli.config.as_deref(), Some("-"));
+    }
+
+    #[test]
+    fn takes_config_from_environment() {
+        env::set_var("MAESTRO_CONFIG", "env.yml");
+
+        let from_env = parse(&[]).unwrap();
+        let from_args = parse(&["-c", "args.yml"]).unwrap();
+
+        env::remove_var("MAESTRO_CONFIG");
+
+        assert_eq!(from_env.config.as_deref(), Some("env.yml"));
+        assert_eq!(from_args.config.as_deref(), Some("args.yml"));
+    }
+
+    #[test]
+    fn rejects_missing_values() {
+        assert_eq!(
+            parse(&["--config"]).unwrap_err(),
+            "option --config requires a value"
+        );
+        assert_eq!(
+            parse(&["check", "-l"]).unwrap_err(),
+            "option -l requires a value"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_options() {
+        assert_eq!(
+            parse(&["--verbose"]).unwrap_err(),
+            "unknown option --verbose"
+        );
+        assert_eq!(parse(&["-x"]).unwrap_err(), "unknown option -x");
+        assert_eq!(parse(&["--foo=bar"]).unwrap_err(), "unknown option --foo");
+    }
+
+    #[test]
+    fn rejects_unexpected_arguments() {
+        assert_eq!(parse(&["run"]).unwrap_err(), "unexpected argument \"run\"");
+        assert_eq!(
+            parse(&["check", "validate"]).unwrap_err(),
+            "unexpected argument \"validate\""
+        );
+        assert_eq!(parse(&["-"]).unwrap_err(), "unexpected argument \"-\"");
+    }
+}
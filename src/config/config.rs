@@ -1,20 +1,51 @@
-use std::{collections::HashMap, env, fs::File, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
 
+use libc::{SIGCHLD, SIGINT, SIGQUIT, SIGTERM};
 use serde::Deserialize;
+use serde_yaml::Value;
 
-use super::config_app::ConfigApp;
+use crate::glob;
+
+use super::{
+    config_app::{deserialize_signals, ConfigApp},
+    config_app_type::ConfigAppType,
+    config_error::{ConfigError, ConfigProblem},
+    config_fragment::ConfigFragment,
+    config_readiness_probe::ConfigReadinessProbe,
+    config_restart_policy::ConfigRestartPolicy,
+    config_source::ConfigSource,
+    config_stop_step::ConfigStopStep,
+};
 
 const CONFIG_FILENAME: &str = "maestro.yml";
 const CONFIG_DIR: &str = "/etc/maestro";
-
-fn default_apps() -> Vec<ConfigApp> {
-    vec![]
-}
+const CONF_D_DIR: &str = "conf.d";
+const CONF_D_PATTERNS: [&str; 2] = ["*.yml", "*.yaml"];
 
 fn default_log_level() -> String {
     "info".to_string()
 }
 
+fn default_stop_timeout() -> u32 {
+    10000
+}
+
+/*
+ * SIGTERM, SIGINT and SIGQUIT stop maestro itself, SIGCHLD is used internally,
+ * the others can not be handled at all
+ */
+fn is_forwardable_signal(signal: i32) -> bool {
+    signal > 0
+        && !signal_hook::consts::FORBIDDEN.contains(&signal)
+        && ![SIGTERM, SIGINT, SIGQUIT, SIGCHLD].contains(&signal)
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub pid: Option<String>,
@@ -22,65 +53,819 @@ pub struct Config {
     #[serde(default = "default_log_level")]
     pub log_level: String,
 
-    #[serde(default = "default_apps")]
+    pub init: Option<bool>,
+
+    #[serde(default = "default_stop_timeout")]
+    pub stop_timeout: u32,
+
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_signals")]
+    pub forward_signals: Vec<i32>,
+
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    // Deserialized one by one, see `load_apps`
+    #[serde(skip)]
     pub apps: Vec<ConfigApp>,
+
+    // The file the config is loaded from
+    #[serde(skip)]
+    pub path: Option<String>,
+
+    // The texts of the loaded files by their paths, to locate problems found by `validate`
+    #[serde(skip)]
+    sources: HashMap<String, ConfigSource>,
+
+    // Problems found while loading, they are reported by `validate` along with its own
+    #[serde(skip)]
+    problems: Vec<ConfigProblem>,
+
+    // Names of the apps that could not be loaded, None if not all of them are known
+    #[serde(skip)]
+    unloaded: Option<HashSet<String>>,
+}
+
+/*
+ * Deserializes each app separately, so the problems of all apps are reported at once.
+ * The names of the apps that could not be deserialized are added to `unloaded`
+ */
+fn load_apps(
+    source: &ConfigSource,
+    apps: &[Value],
+    unloaded: &mut Option<HashSet<String>>,
+) -> (Vec<ConfigApp>, Vec<ConfigProblem>) {
+    let mut result: Vec<ConfigApp> = vec![];
+    let mut problems: Vec<ConfigProblem> = vec![];
+
+    for (position, value) in apps.iter().enumerate() {
+        match source.deserialize::<ConfigApp>(value, &format!("apps[{}]", position)) {
+            Ok(app) => result.push(ConfigApp { position, ..app }),
+            Err(problem) => {
+                problems.push(problem);
+
+                match value.get("name").and_then(|x| x.as_str()) {
+                    Some(name) => {
+                        if let Some(names) = unloaded {
+                            names.insert(name.to_string());
+                        }
+                    }
+                    None => *unloaded = None,
+                }
+            }
+        }
+    }
+
+    (result, problems)
 }
 
 impl Config {
-    pub fn new() -> Self {
-        let pwd = env::current_dir().expect("unable to get cwd");
+    /*
+     * Loads the config from the given path ("-" means stdin),
+     * otherwise looks for it in the current working directory and then in CONFIG_DIR
+     */
+    pub fn new(path: Option<String>) -> Result<Self, ConfigError> {
+        match path.as_deref() {
+            Some("-") => {
+                return Self::from_reader(io::stdin(), "<stdin>".to_string(), Path::new(""))
+            }
+            Some(path) => {
+                let file = File::open(path).map_err(|error| {
+                    ConfigError::new(
+                        None,
+                        ConfigProblem::new(format!(
+                            "unable to open config file {}, {}",
+                            path, error
+                        )),
+                    )
+                })?;
+
+                return Self::from_reader(
+                    file,
+                    path.to_string(),
+                    Path::new(path).parent().unwrap_or(Path::new("")),
+                );
+            }
+            None => {}
+        }
+
+        let pwd = env::current_dir().map_err(|error| {
+            ConfigError::new(
+                None,
+                ConfigProblem::new(format!("unable to get cwd, {}", error)),
+            )
+        })?;
+
         let cwd_config_path = Path::new(pwd.as_path()).join(CONFIG_FILENAME);
 
         if let Ok(file) = File::open(&cwd_config_path) {
-            let config: Config = serde_yaml::from_reader(file).unwrap();
-
-            return config;
+            return Self::from_reader(file, cwd_config_path.display().to_string(), &pwd);
         }
 
         let etc_config_path = Path::new(CONFIG_DIR).join(CONFIG_FILENAME);
 
-        let file = File::open(&etc_config_path).unwrap_or_else(|_| {
-            panic!(
-                "config file not found, checked: {} and {}",
-                cwd_config_path.to_str().unwrap(),
-                etc_config_path.to_str().unwrap()
+        let file = File::open(&etc_config_path).map_err(|_| {
+            ConfigError::new(
+                None,
+                ConfigProblem::new(format!(
+                    "config file not found, checked: {} and {}",
+                    cwd_config_path.display(),
+                    etc_config_path.display()
+                )),
             )
-        });
+        })?;
+
+        Self::from_reader(
+            file,
+            etc_config_path.display().to_string(),
+            Path::new(CONFIG_DIR),
+        )
+    }
+
+    /*
+     * `dir` is the directory of the config file, included files are looked up relative to it
+     */
+    fn from_reader(mut reader: impl Read, path: String, dir: &Path) -> Result<Self, ConfigError> {
+        let to_error = |problem: ConfigProblem| ConfigError::new(Some(path.to_owned()), problem);
+
+        let mut text = String::new();
 
-        let config: Config = serde_yaml::from_reader(file).unwrap();
+        reader
+            .read_to_string(&mut text)
+            .map_err(|error| to_error(ConfigProblem::new(format!("unable to read, {}", error))))?;
 
-        config
+        let source = ConfigSource::new(text);
+        let value = source.parse().map_err(|problems| ConfigError {
+            path: Some(path.to_owned()),
+            problems,
+        })?;
+
+        let apps = match value.get("apps") {
+            Some(apps) => source.deserialize::<Vec<Value>>(apps, "apps"),
+            None => Ok(vec![]),
+        };
+
+        let mut unloaded = Some(HashSet::new());
+
+        let (apps, mut problems) = match apps {
+            Ok(apps) => load_apps(&source, &apps, &mut unloaded),
+            Err(problem) => {
+                unloaded = None;
+
+                (vec![], vec![problem])
+            }
+        };
+
+        let mut config = match source.deserialize::<Config>(&value, "") {
+            Ok(config) => config,
+            Err(problem) => {
+                problems.insert(0, problem);
+
+                return Err(ConfigError {
+                    path: Some(path),
+                    problems,
+                });
+            }
+        };
+
+        config.apps = apps;
+        config.sources.insert(path.to_owned(), source);
+        config.path = Some(path);
+        config.problems = problems;
+        config.unloaded = unloaded;
+        config.load_includes(dir);
+
+        Ok(config)
     }
 
-    pub fn validate(self) -> Self {
+    /*
+     * Appends apps of the included files (in the listed order) and then of conf.d (sorted by name).
+     * A file matched several times is loaded once
+     */
+    fn load_includes(&mut self, dir: &Path) {
+        let mut problems: Vec<ConfigProblem> = vec![];
+        let mut files: Vec<PathBuf> = vec![];
+
+        for (index, pattern) in self.include.iter().enumerate() {
+            match glob::expand(&dir.join(pattern)) {
+                Ok(paths) => files.extend(paths),
+                Err(message) => problems.push(ConfigProblem {
+                    field: Some("include".to_string()),
+                    location: self.locate(None, &format!("include[{}]", index)),
+                    ..ConfigProblem::new(message)
+                }),
+            }
+        }
+
+        let mut conf_d_files: Vec<PathBuf> = vec![];
+
+        for pattern in CONF_D_PATTERNS {
+            match glob::expand(&dir.join(CONF_D_DIR).join(pattern)) {
+                Ok(paths) => conf_d_files.extend(paths),
+                Err(message) => {
+                    // both patterns fail the same way, e.g. if the directory is not readable
+                    problems.push(ConfigProblem::new(message));
+                    break;
+                }
+            }
+        }
+
+        conf_d_files.sort();
+        files.extend(conf_d_files);
+
+        let mut loaded: HashSet<PathBuf> = HashSet::new();
+
+        if let Some(path) = self.path.as_ref().and_then(|x| fs::canonicalize(x).ok()) {
+            loaded.insert(path);
+        }
+
+        for file_path in files {
+            if !loaded.insert(fs::canonicalize(&file_path).unwrap_or(file_path.to_owned())) {
+                continue;
+            }
+
+            let source = file_path.display().to_string();
+
+            let fragment = fs::read_to_string(&file_path)
+                .map_err(|error| vec![ConfigProblem::new(format!("unable to open, {}", error))])
+                .map(ConfigSource::new)
+                .and_then(|file_source| {
+                    let value = file_source.parse()?;
+
+                    let fragment = file_source
+                        .deserialize::<ConfigFragment>(&value, "")
+                        .map_err(|problem| vec![problem])?;
+
+                    Ok((fragment, file_source))
+                });
+
+            let (fragment, file_source) = match fragment {
+                Ok(result) => result,
+                Err(file_problems) => {
+                    // the apps of the file are not known at all
+                    self.unloaded = None;
+
+                    for problem in file_problems {
+                        problems.push(ConfigProblem {
+                            file: Some(source.to_owned()),
+                            ..problem
+                        });
+                    }
+
+                    continue;
+                }
+            };
+
+            let (apps, app_problems) = load_apps(&file_source, &fragment.apps, &mut self.unloaded);
+
+            for app in apps {
+                self.apps.push(ConfigApp {
+                    source: Some(source.to_owned()),
+                    ..app
+                });
+            }
+
+            for problem in app_problems {
+                problems.push(ConfigProblem {
+                    file: Some(source.to_owned()),
+                    ..problem
+                });
+            }
+
+            self.sources.insert(source, file_source);
+        }
+
+        self.problems.extend(problems);
+    }
+
+    /*
+     * Returns the location of the field in the given file, None means the main config file
+     */
+    fn locate(&self, file: Option<&String>, path: &str) -> Option<(usize, usize)> {
+        self.sources
+            .get(file.or(self.path.as_ref())?)
+            .and_then(|source| source.locate(path))
+    }
+
+    pub fn validate(mut self) -> Result<Self, ConfigError> {
+        let problems = self.find_problems();
+
+        if !problems.is_empty() {
+            return Err(ConfigError {
+                path: self.path,
+                problems,
+            });
+        }
+
+        Ok(self)
+    }
+
+    /*
+     * Returns the problems found while loading followed by the ones of the loaded apps,
+     * so an app that could not be loaded does not hide the problems of the others
+     */
+    pub fn find_problems(&mut self) -> Vec<ConfigProblem> {
         let mut apps_map: HashMap<String, &ConfigApp> = HashMap::new();
+        let mut problems: Vec<ConfigProblem> = std::mem::take(&mut self.problems);
+
+        for (index, signal) in self.forward_signals.iter().enumerate() {
+            if !is_forwardable_signal(*signal) {
+                problems.push(ConfigProblem {
+                    field: Some("forward_signals".to_string()),
+                    location: self.locate(None, &format!("forward_signals[{}]", index)),
+                    ..ConfigProblem::new(format!("signal {} can not be forwarded", signal))
+                });
+            }
+        }
+
+        let mut report = |app: &ConfigApp, field: &str, message: String| {
+            let path = format!("apps[{}].{}", app.position, field);
+
+            problems.push(ConfigProblem {
+                location: self.locate(app.source.as_ref(), &path),
+                ..ConfigProblem::for_app(app, field, message)
+            });
+        };
 
         for app in self.apps.iter() {
+            let name = app.name.as_str();
+
             if app.command.is_empty() {
-                panic!("command is not presented for app: \"{}\"", app.name);
+                report(app, "command", "must not be empty".to_string());
+            }
+
+            if app.restart_backoff.multiplier < 1.0 {
+                report(
+                    app,
+                    "restart_backoff.multiplier",
+                    "must be at least 1".to_string(),
+                );
             }
 
-            if apps_map.contains_key(&app.name) {
-                panic!("App names must be unique");
+            if !(0.0..=1.0).contains(&app.restart_backoff.jitter) {
+                report(
+                    app,
+                    "restart_backoff.jitter",
+                    "must be between 0 and 1".to_string(),
+                );
+            }
+
+            if app.start_limit.burst == 0 {
+                report(app, "start_limit.burst", "must be at least 1".to_string());
+            }
+
+            if app.success_exit_codes.is_empty() {
+                report(app, "success_exit_codes", "must not be empty".to_string());
+            }
+
+            if let ConfigReadinessProbe::ExitCode { exit_code } = app.ready {
+                if !app.success_exit_codes.contains(&exit_code) {
+                    report(
+                        app,
+                        "ready.exit_code",
+                        "must be one of success_exit_codes".to_string(),
+                    );
+                }
+            }
+
+            if app.app_type == ConfigAppType::Oneshot {
+                if !matches!(
+                    app.ready,
+                    ConfigReadinessProbe::None | ConfigReadinessProbe::ExitCode { .. }
+                ) {
+                    report(
+                        app,
+                        "ready",
+                        "only exit_code readiness probe is allowed for oneshot app".to_string(),
+                    );
+                }
+            } else if app.timeout.is_some() {
+                report(
+                    app,
+                    "timeout",
+                    "is allowed only for oneshot app".to_string(),
+                );
+            }
+
+            if let Some(liveness) = &app.liveness {
+                if !matches!(
+                    liveness.probe,
+                    ConfigReadinessProbe::Command { .. } | ConfigReadinessProbe::Http { .. }
+                ) {
+                    report(
+                        app,
+                        "liveness",
+                        "only command and http liveness probes are allowed".to_string(),
+                    );
+                }
+
+                if liveness.failure_threshold == 0 {
+                    report(
+                        app,
+                        "liveness.failure_threshold",
+                        "must be at least 1".to_string(),
+                    );
+                }
+
+                if app.app_type == ConfigAppType::Oneshot || app.schedule.is_some() {
+                    report(
+                        app,
+                        "liveness",
+                        "is not allowed for oneshot and scheduled app".to_string(),
+                    );
+                }
+            }
+
+            if app.schedule.is_some() && !matches!(app.restart, ConfigRestartPolicy::Never) {
+                report(
+                    app,
+                    "restart",
+                    "scheduled app can not be restarted".to_string(),
+                );
+            }
+
+            for (index, signal) in app.forward_signals.iter().enumerate() {
+                if !is_forwardable_signal(*signal) {
+                    report(
+                        app,
+                        &format!("forward_signals[{}]", index),
+                        format!("signal {} can not be forwarded", signal),
+                    );
+                }
+            }
+
+            if app.stop_command.is_some() && app.stop_sequence.is_some() {
+                report(
+                    app,
+                    "stop_command",
+                    "can not be used together with stop_sequence".to_string(),
+                );
+            }
+
+            if app.stop_command.as_ref().is_some_and(|x| x.is_empty()) {
+                report(app, "stop_command", "must not be empty".to_string());
+            }
+
+            if let Some(steps) = &app.stop_sequence {
+                if steps.is_empty() {
+                    report(app, "stop_sequence", "must not be empty".to_string());
+                }
+
+                for (index, step) in steps.iter().enumerate() {
+                    if let ConfigStopStep::Command { command, .. } = step {
+                        if command.is_empty() {
+                            report(
+                                app,
+                                &format!("stop_sequence[{}].command", index),
+                                "must not be empty".to_string(),
+                            );
+                        }
+                    }
+                }
+            }
+
+            if app.pre_stop.as_ref().is_some_and(|x| x.is_empty()) {
+                report(app, "pre_stop", "must not be empty".to_string());
+            }
+
+            /*
+             * The pre-stop hook is counted inside the timeout of the first stop step
+             */
+            let first_step_timeout = app
+                .stop_sequence
+                .as_ref()
+                .and_then(|steps| steps.first())
+                .and_then(|step| match step {
+                    ConfigStopStep::Signal { wait, .. } => *wait,
+                    ConfigStopStep::Command { wait, .. } => *wait,
+                })
+                .unwrap_or(app.stop_timeout.unwrap_or(self.stop_timeout));
+
+            if app.stop_delay > 0 && app.stop_delay >= first_step_timeout {
+                report(
+                    app,
+                    "stop_delay",
+                    format!("must be less than stop timeout ({} ms)", first_step_timeout),
+                );
+            } else if app.pre_stop.is_some() && first_step_timeout == 0 {
+                report(
+                    app,
+                    "pre_stop",
+                    "requires stop timeout greater than 0 ms".to_string(),
+                );
+            }
+
+            if let Some(other) = apps_map.get(name) {
+                let get_source = |app: &ConfigApp| {
+                    app.source
+                        .to_owned()
+                        .or(self.path.to_owned())
+                        .unwrap_or_default()
+                };
+
+                report(
+                    app,
+                    "name",
+                    format!(
+                        "must be unique, the app is defined in both {} and {}",
+                        get_source(other),
+                        get_source(app)
+                    ),
+                );
+
+                continue;
             }
 
             apps_map.insert(app.name.to_owned(), app);
         }
 
         for app in self.apps.iter() {
-            for dep in app.depends_on.iter() {
-                // TODO check cycles
+            for (index, dep) in app.depends_on.iter().enumerate() {
+                let dep_name = dep.get_name();
+                let field = format!("depends_on[{}]", index);
 
-                if app.name == *dep {
-                    panic!("dependence on oneself: \"{}\"", dep);
+                if app.name == *dep_name {
+                    report(app, &field, "dependence on oneself".to_string());
                 }
 
-                if apps_map.get(dep).is_none() {
-                    panic!("unknown dependency: \"{}\"", dep);
+                /*
+                 * An optional dependency may refer to an app that is not configured.
+                 * An app that could not be loaded is configured, its problem is already reported
+                 */
+                let unloaded = self.unloaded.as_ref().is_none_or(|x| x.contains(dep_name));
+
+                if !apps_map.contains_key(dep_name) && dep.is_required() && !unloaded {
+                    report(app, &field, format!("unknown dependency \"{}\"", dep_name));
                 }
             }
         }
 
-        self
+        if let Some(cycle) = find_cycle(&apps_map, &self.apps) {
+            report(
+                apps_map[&cycle[0]],
+                "depends_on",
+                format!("dependency cycle detected: {}", cycle.join(" -> ")),
+            );
+        }
+
+        problems
+    }
+}
+
+/*
+ * Returns the first dependency cycle found, e.g. ["a", "b", "a"].
+ * Apps are visited in the config order, so the result is stable
+ */
+fn find_cycle(apps_map: &HashMap<String, &ConfigApp>, apps: &[ConfigApp]) -> Option<Vec<String>> {
+    fn visit(
+        name: &String,
+        apps_map: &HashMap<String, &ConfigApp>,
+        path: &mut Vec<String>,
+        visited: &mut HashSet<String>,
+    ) -> Option<Vec<String>> {
+        if let Some(position) = path.iter().position(|x| x == name) {
+            let mut cycle = path[position..].to_vec();
+            cycle.push(name.to_owned());
+
+            return Some(cycle);
+        }
+
+        if !visited.insert(name.to_owned()) {
+            return None;
+        }
+
+        let app = apps_map.get(name)?;
+
+        path.push(name.to_owned());
+
+        // dependence on oneself is reported separately
+        for dep in app.depends_on.iter().filter(|dep| dep.get_name() != name) {
+            if let Some(cycle) = visit(dep.get_name(), apps_map, path, visited) {
+                return Some(cycle);
+            }
+        }
+
+        path.pop();
+
+        None
+    }
+
+    let mut visited: HashSet<String> = HashSet::new();
+
+    apps.iter()
+        .find_map(|app| visit(&app.name, apps_map, &mut vec![], &mut visited))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(text: &str) -> Result<Config, ConfigError> {
+        Config::from_reader(
+            text.as_bytes(),
+            "maestro.yml".to_string(),
+            Path::new("/nonexistent"),
+        )
+        .and_then(Config::validate)
+    }
+
+    fn get_messages(error: ConfigError) -> Vec<String> {
+        error
+            .problems
+            .iter()
+            .map(|problem| problem.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn accepts_zero_stop_timeout_without_pre_stop() {
+        let config = load(
+            r#"
+            stop_timeout: 0
+            apps:
+              - name: a
+                command: ["a"]
+              - name: b
+                command: ["b"]
+                stop_sequence: [{signal: TERM, wait: 0}]
+            "#,
+        );
+
+        assert!(config.is_ok());
+    }
+
+    #[test]
+    fn requires_stop_timeout_longer_than_pre_stop() {
+        let error = load(
+            r#"
+            apps:
+              - name: a
+                command: ["a"]
+                stop_timeout: 100
+                stop_delay: 100
+              - name: b
+                command: ["b"]
+                stop_timeout: 0
+                pre_stop: ["sleep", "1"]
+            "#,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            get_messages(error),
+            vec![
+                "line 6, column 29: app \"a\", stop_delay: must be less than stop timeout (100 ms)",
+                "line 10, column 27: app \"b\", pre_stop: requires stop timeout greater than 0 ms",
+            ]
+        );
+    }
+
+    #[test]
+    fn accepts_numbers_from_variables() {
+        std::env::set_var("MAESTRO_TEST_STOP_TIMEOUT", "3000");
+
+        let config = load(
+            r#"
+            stop_timeout: ${MAESTRO_TEST_STOP_TIMEOUT}
+            apps:
+              - name: a
+                command: ["a"]
+                stop_timeout: ${MAESTRO_TEST_MISSING_VARIABLE:-5000}
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.stop_timeout, 3000);
+        assert_eq!(config.apps[0].stop_timeout, Some(5000));
+    }
+
+    fn get_cycle(text: &str) -> Option<Vec<String>> {
+        let config = Config::from_reader(
+            text.as_bytes(),
+            "maestro.yml".to_string(),
+            Path::new("/nonexistent"),
+        )
+        .unwrap();
+        let apps_map: HashMap<String, &ConfigApp> = config
+            .apps
+            .iter()
+            .map(|app| (app.name.to_owned(), app))
+            .collect();
+
+        find_cycle(&apps_map, &config.apps)
+    }
+
+    #[test]
+    fn finds_dependency_cycles() {
+        let cycle = get_cycle(
+            r#"
+            apps:
+              - name: a
+                command: ["a"]
+                depends_on: [b]
+              - name: b
+                command: ["b"]
+                depends_on: [a]
+            "#,
+        );
+
+        assert_eq!(cycle, Some(vec!["a".into(), "b".into(), "a".into()]));
+
+        let cycle = get_cycle(
+            r#"
+            apps:
+              - name: a
+                command: ["a"]
+              - name: b
+                command: ["b"]
+                depends_on: [a, c]
+              - name: c
+                command: ["c"]
+                depends_on: [d]
+              - name: d
+                command: ["d"]
+                depends_on: [b]
+            "#,
+        );
+
+        assert_eq!(
+            cycle,
+            Some(vec!["b".into(), "c".into(), "d".into(), "b".into()])
+        );
+    }
+
+    #[test]
+    fn finds_no_cycle_in_shared_dependencies() {
+        let cycle = get_cycle(
+            r#"
+            apps:
+              - name: a
+                command: ["a"]
+                depends_on: [b, c, missing]
+              - name: b
+                command: ["b"]
+                depends_on: [d]
+              - name: c
+                command: ["c"]
+                depends_on: [d]
+              - name: d
+                command: ["d"]
+            "#,
+        );
+
+        assert_eq!(cycle, None);
+    }
+
+    #[test]
+    fn reports_dependence_on_oneself_apart_from_cycles() {
+        let error = load(
+            r#"
+            apps:
+              - name: a
+                command: ["a"]
+                depends_on: [a]
+              - name: b
+                command: ["b"]
+                depends_on: [c]
+              - name: c
+                command: ["c"]
+                depends_on: [b]
+            "#,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            get_messages(error),
+            vec![
+                "line 5, column 30: app \"a\", depends_on[0]: dependence on oneself",
+                "line 8, column 29: app \"b\", depends_on: dependency cycle detected: b -> c -> b",
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_apps_next_to_ones_that_could_not_be_loaded() {
+        let error = load(
+            r#"
+            apps:
+              - name: db
+                command: ["db"]
+                stop_timeout: soon
+              - name: web
+                command: []
+                depends_on: [db, cache]
+            "#,
+        )
+        .unwrap_err();
+
+        // "db" is configured, only its own problem is reported
+        assert_eq!(
+            get_messages(error),
+            vec![
+                "line 5, column 31: apps[0].stop_timeout: invalid type: string \"soon\", expected u32",
+                "line 7, column 26: app \"web\", command: must not be empty",
+                "line 8, column 34: app \"web\", depends_on[1]: unknown dependency \"cache\"",
+            ]
+        );
     }
 }
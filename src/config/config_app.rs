@@ -1,20 +1,36 @@
-use libc::{SIGINT, SIGTERM};
-use serde::Deserialize;
+use std::fmt;
+
+use libc::SIGTERM;
+use serde::{
+    de::{self, Visitor},
+    Deserialize,
+};
 use serde_yaml::Value;
 
-use crate::user::get_uid_from_username;
+use crate::{cron::CronExpression, signal, user::get_uid_from_username};
 
-use super::config_readiness_probe::ConfigReadinessProbe;
+use super::{
+    config_app_type::ConfigAppType, config_dependency::ConfigDependency,
+    config_failure_policy::ConfigFailurePolicy, config_liveness_probe::ConfigLivenessProbe,
+    config_overlap_policy::ConfigOverlapPolicy, config_readiness_probe::ConfigReadinessProbe,
+    config_restart_backoff::ConfigRestartBackoff, config_restart_policy::ConfigRestartPolicy,
+    config_schedule::ConfigSchedule, config_start_limit::ConfigStartLimit,
+    config_stop_step::ConfigStopStep,
+};
 
 fn default_app_user() -> u32 {
     unsafe { libc::geteuid() }
 }
 
+fn default_app_type() -> ConfigAppType {
+    ConfigAppType::Service
+}
+
 fn default_app_signal() -> i32 {
     SIGTERM
 }
 
-fn default_depends_on() -> Vec<String> {
+fn default_depends_on() -> Vec<ConfigDependency> {
     vec![]
 }
 
@@ -22,72 +38,200 @@ fn default_ready() -> ConfigReadinessProbe {
     ConfigReadinessProbe::None
 }
 
-fn deserialize_and_get_uid<'de, D>(deserializer: D) -> Result<u32, D::Error>
+fn default_restart() -> ConfigRestartPolicy {
+    ConfigRestartPolicy::Never
+}
+
+fn default_success_exit_codes() -> Vec<i32> {
+    vec![0]
+}
+
+fn default_on_failure() -> ConfigFailurePolicy {
+    ConfigFailurePolicy::Shutdown
+}
+
+fn default_overlap() -> ConfigOverlapPolicy {
+    ConfigOverlapPolicy::Skip
+}
+
+/*
+ * Converts a scalar by the function. The conversion happens while the value
+ * is being deserialized, so the error is reported at the location of the value
+ */
+struct ScalarVisitor<F> {
+    expected: &'static str,
+    parse: F,
+}
+
+impl<F> ScalarVisitor<F> {
+    fn convert<T, E>(self, value: Value) -> Result<T, E>
+    where
+        F: FnOnce(Value) -> Result<T, String>,
+        E: de::Error,
+    {
+        (self.parse)(value).map_err(E::custom)
+    }
+}
+
+impl<'de, T, F> Visitor<'de> for ScalarVisitor<F>
+where
+    F: FnOnce(Value) -> Result<T, String>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.expected)
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<T, E> {
+        self.convert(Value::Bool(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<T, E> {
+        self.convert(Value::Number(value.into()))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<T, E> {
+        self.convert(Value::Number(value.into()))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<T, E> {
+        self.convert(Value::Number(value.into()))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        self.convert(Value::String(value.to_string()))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<T, E> {
+        self.convert(Value::Null)
+    }
+}
+
+fn deserialize_scalar<'de, D, T>(
+    deserializer: D,
+    expected: &'static str,
+    parse: impl FnOnce(Value) -> Result<T, String>,
+) -> Result<T, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    let value: Value = Deserialize::deserialize(deserializer)?;
+    deserializer.deserialize_any(ScalarVisitor { expected, parse })
+}
 
-    let uid = match value {
+fn parse_uid(value: Value) -> Result<u32, String> {
+    match value {
         Value::String(string_value) => {
             let first = match string_value.chars().next() {
                 Some(value) => value,
-                None => panic!("user value is empty"),
+                None => return Err("user value is empty".to_string()),
             };
 
             // linux user must start with an alphabetic character
             // so if the first char of passed value is number we consider it as number
             if first.is_ascii_digit() {
-                match string_value.parse::<u32>() {
-                    Ok(value) => value,
-                    Err(_) => panic!("unable to parse string: {}", string_value),
-                }
+                string_value
+                    .parse::<u32>()
+                    .map_err(|_| format!("invalid uid \"{}\"", string_value))
             } else {
                 get_uid_from_username(string_value.as_str())
             }
         }
-        Value::Number(number) => match number.as_u64() {
-            Some(num_u64) => u32::try_from(num_u64).expect("provided uid is invalid (too large)"),
-            None => panic!("unable to parse uid"),
+        Value::Number(number) => number
+            .as_u64()
+            .and_then(|num_u64| u32::try_from(num_u64).ok())
+            .ok_or_else(|| format!("invalid uid {}", number)),
+        _ => Err("unable to parse user value, expected string or number".to_string()),
+    }
+}
+
+fn deserialize_and_get_uid<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_scalar(deserializer, "user name or uid", parse_uid)
+}
+
+fn parse_signal(value: Value) -> Result<i32, String> {
+    match value {
+        Value::String(string_value) => signal::parse(&string_value),
+        Value::Number(number) => match number.as_i64() {
+            Some(num_i64) => signal::validate(num_i64),
+            None => Err(format!("invalid signal number {}", number)),
         },
-        _ => {
-            panic!("unable to parse user value, expected string or number");
-        }
-    };
+        _ => Err("unable to parse signal, expected string or number".to_string()),
+    }
+}
 
-    Ok(uid)
+pub(super) fn deserialize_signal<'de, D>(deserializer: D) -> Result<i32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_scalar(deserializer, "signal name or number", parse_signal)
 }
 
-fn deserialize_signal<'de, D>(deserializer: D) -> Result<i32, D::Error>
+pub(super) fn deserialize_optional_signal<'de, D>(deserializer: D) -> Result<Option<i32>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    let value: Value = Deserialize::deserialize(deserializer)?;
+    deserialize_signal(deserializer).map(Some)
+}
 
-    let signal = match value {
-        Value::String(string_value) => match string_value.to_lowercase().as_str() {
-            "sigterm" | "term" => SIGTERM,
-            "sigint" | "int" => SIGINT,
-            _ => panic!("unknown signal name {}", string_value),
-        },
-        Value::Number(number) => match number.as_i64() {
-            Some(num_i64) => {
-                i32::try_from(num_i64).expect("provided signal is invalid (too large)")
-            }
-            None => panic!("unable to parse signal number"),
+// A list item, so the problem is reported at the signal rather than at the list
+#[derive(Deserialize)]
+struct ConfigSignal(#[serde(deserialize_with = "deserialize_signal")] i32);
+
+pub(super) fn deserialize_signals<'de, D>(deserializer: D) -> Result<Vec<i32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let signals: Vec<ConfigSignal> = Deserialize::deserialize(deserializer)?;
+
+    Ok(signals.into_iter().map(|signal| signal.0).collect())
+}
+
+fn parse_schedule(value: Value) -> Result<Option<ConfigSchedule>, String> {
+    let schedule = match value {
+        Value::String(string_value) => ConfigSchedule::Cron(CronExpression::parse(&string_value)?),
+        Value::Number(number) => match number.as_u64() {
+            Some(num_u64) if num_u64 > 0 => ConfigSchedule::Interval(
+                u32::try_from(num_u64).map_err(|_| "schedule interval is too large")?,
+            ),
+            _ => return Err("schedule interval must be a positive number".to_string()),
         },
         _ => {
-            panic!("unable to parse signal, expected string or number");
+            return Err(
+                "unable to parse schedule, expected cron expression or interval in milliseconds"
+                    .to_string(),
+            );
         }
     };
 
-    Ok(signal)
+    Ok(Some(schedule))
+}
+
+fn deserialize_schedule<'de, D>(deserializer: D) -> Result<Option<ConfigSchedule>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_scalar(
+        deserializer,
+        "cron expression or interval in milliseconds",
+        parse_schedule,
+    )
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ConfigApp {
     pub name: String,
     pub command: Vec<String>,
+
+    #[serde(default = "default_app_type")]
+    #[serde(rename(deserialize = "type"))]
+    pub app_type: ConfigAppType,
+
+    pub timeout: Option<u32>,
+
     pub stdout: Option<String>,
     pub stderr: Option<String>,
 
@@ -101,8 +245,57 @@ pub struct ConfigApp {
     pub uid: u32,
 
     #[serde(default = "default_depends_on")]
-    pub depends_on: Vec<String>,
+    pub depends_on: Vec<ConfigDependency>,
 
     #[serde(default = "default_ready")]
     pub ready: ConfigReadinessProbe,
+
+    pub liveness: Option<ConfigLivenessProbe>,
+
+    #[serde(default = "default_restart")]
+    pub restart: ConfigRestartPolicy,
+
+    #[serde(default)]
+    pub restart_backoff: ConfigRestartBackoff,
+
+    #[serde(default)]
+    pub start_limit: ConfigStartLimit,
+
+    pub stop_timeout: Option<u32>,
+
+    pub stop_command: Option<Vec<String>>,
+    pub stop_sequence: Option<Vec<ConfigStopStep>>,
+
+    pub pre_stop: Option<Vec<String>>,
+
+    #[serde(default)]
+    pub stop_delay: u32,
+
+    #[serde(default = "default_on_failure")]
+    pub on_failure: ConfigFailurePolicy,
+
+    #[serde(default = "default_success_exit_codes")]
+    pub success_exit_codes: Vec<i32>,
+
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_schedule")]
+    pub schedule: Option<ConfigSchedule>,
+
+    #[serde(default = "default_overlap")]
+    pub overlap: ConfigOverlapPolicy,
+
+    #[serde(default)]
+    pub process_group: bool,
+
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_signals")]
+    pub forward_signals: Vec<i32>,
+
+    // The included file the app is defined in, None for the main config file
+    #[serde(skip)]
+    pub source: Option<String>,
+
+    // The index of the app in its file, to locate problems
+    #[serde(skip)]
+    pub position: usize,
 }
@@ -0,0 +1,99 @@
+use std::fmt;
+
+use serde::{
+    de::{value::MapAccessDeserializer, Error, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+use super::config_dependency_condition::ConfigDependencyCondition;
+
+fn default_dependency_condition() -> ConfigDependencyCondition {
+    ConfigDependencyCondition::ServiceReady
+}
+
+fn default_dependency_required() -> bool {
+    true
+}
+
+#[derive(Debug, Clone)]
+pub enum ConfigDependency {
+    Name(String),
+    Extended {
+        name: String,
+        condition: ConfigDependencyCondition,
+        required: bool,
+    },
+}
+
+#[derive(Deserialize)]
+struct ConfigDependencyOptions {
+    name: String,
+
+    #[serde(default = "default_dependency_condition")]
+    condition: ConfigDependencyCondition,
+
+    #[serde(default = "default_dependency_required")]
+    required: bool,
+}
+
+/*
+ * A dependency is either the name of an app or a map with the name and other options
+ */
+struct ConfigDependencyVisitor;
+
+impl<'de> Visitor<'de> for ConfigDependencyVisitor {
+    type Value = ConfigDependency;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "app name or map with name, condition and required")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(ConfigDependency::Name(value.to_string()))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let ConfigDependencyOptions {
+            name,
+            condition,
+            required,
+        } = ConfigDependencyOptions::deserialize(MapAccessDeserializer::new(map))?;
+
+        Ok(ConfigDependency::Extended {
+            name,
+            condition,
+            required,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for ConfigDependency {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ConfigDependencyVisitor)
+    }
+}
+
+impl ConfigDependency {
+    pub fn get_name(&self) -> &String {
+        match self {
+            ConfigDependency::Name(name) => name,
+            ConfigDependency::Extended { name, .. } => name,
+        }
+    }
+
+    pub fn is_required(&self) -> bool {
+        match self {
+            ConfigDependency::Name(_) => true,
+            ConfigDependency::Extended { required, .. } => *required,
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigDependencyCondition {
+    ServiceStarted,
+    ServiceReady,
+    CompletedSuccessfully,
+}
@@ -0,0 +1,127 @@
+use std::fmt;
+
+use super::config_app::ConfigApp;
+
+/*
+ * The exit code of maestro if the config can not be loaded, EX_CONFIG from sysexits.h
+ */
+pub const CONFIG_ERROR_EXIT_CODE: i32 = 78;
+
+#[derive(Debug)]
+pub struct ConfigProblem {
+    // Set if the problem is in an included file
+    pub file: Option<String>,
+
+    pub app: Option<String>,
+    pub field: Option<String>,
+
+    // Line and column, both start from 1
+    pub location: Option<(usize, usize)>,
+
+    pub message: String,
+}
+
+impl ConfigProblem {
+    pub fn new(message: String) -> Self {
+        Self {
+            file: None,
+            app: None,
+            field: None,
+            location: None,
+            message,
+        }
+    }
+
+    pub fn for_app(app: &ConfigApp, field: &str, message: String) -> Self {
+        Self {
+            file: app.source.to_owned(),
+            app: Some(app.name.to_owned()),
+            field: Some(field.to_owned()),
+            location: None,
+            message,
+        }
+    }
+}
+
+impl From<serde_yaml::Error> for ConfigProblem {
+    fn from(error: serde_yaml::Error) -> Self {
+        let message = error.to_string();
+
+        let location = error
+            .location()
+            .map(|location| (location.line(), location.column()));
+
+        /*
+         * serde_yaml appends the location to the message, it is printed separately
+         */
+        let message = match location {
+            Some((line, column)) => {
+                message.replacen(&format!(" at line {} column {}", line, column), "", 1)
+            }
+            None => message,
+        };
+
+        Self {
+            file: None,
+            app: None,
+            field: None,
+            location,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}: ", file)?;
+        }
+
+        if let Some((line, column)) = self.location {
+            write!(f, "line {}, column {}: ", line, column)?;
+        }
+
+        if let Some(app) = &self.app {
+            write!(f, "app \"{}\", ", app)?;
+        }
+
+        if let Some(field) = &self.field {
+            write!(f, "{}: ", field)?;
+        }
+
+        write!(f, "{}", self.message)
+    }
+}
+
+/*
+ * All problems found in the config, they are reported at once
+ */
+#[derive(Debug)]
+pub struct ConfigError {
+    pub path: Option<String>,
+    pub problems: Vec<ConfigProblem>,
+}
+
+impl ConfigError {
+    pub fn new(path: Option<String>, problem: ConfigProblem) -> Self {
+        Self {
+            path,
+            problems: vec![problem],
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "invalid config {}:", path)?,
+            None => write!(f, "invalid config:")?,
+        }
+
+        for problem in self.problems.iter() {
+            write!(f, "\n  - {}", problem)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,13 @@
+use serde::Deserialize;
+use serde_yaml::Value;
+
+/*
+ * An included file (see `include` and conf.d), it may define only apps.
+ * Apps are deserialized one by one to report the problems of all of them
+ */
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFragment {
+    #[serde(default)]
+    pub apps: Vec<Value>,
+}
@@ -0,0 +1,33 @@
+use serde::Deserialize;
+
+use super::{config_liveness_action::ConfigLivenessAction, ConfigReadinessProbe};
+
+fn default_liveness_timeout() -> u32 {
+    1000
+}
+
+fn default_liveness_failure_threshold() -> u32 {
+    3
+}
+
+fn default_liveness_action() -> ConfigLivenessAction {
+    ConfigLivenessAction::Restart
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfigLivenessProbe {
+    #[serde(flatten)]
+    pub probe: ConfigReadinessProbe,
+
+    #[serde(default = "default_liveness_timeout")]
+    pub timeout: u32,
+
+    #[serde(default = "default_liveness_failure_threshold")]
+    pub failure_threshold: u32,
+
+    #[serde(default)]
+    pub initial_delay: u32,
+
+    #[serde(default = "default_liveness_action")]
+    pub action: ConfigLivenessAction,
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConfigOverlapPolicy {
+    Skip,
+    Queue,
+    KillPrevious,
+}
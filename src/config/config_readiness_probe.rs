@@ -1,15 +1,23 @@
-use serde::Deserialize;
+use std::fmt;
+
+use serde::{
+    de::{value::MapAccessDeserializer, Error, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
 
 fn default_readiness_probe_period() -> u32 {
     1000
 }
 
+fn default_readiness_probe_timeout() -> u32 {
+    10000
+}
+
 fn default_readiness_probe_http_method() -> String {
     "GET".to_string()
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(untagged)]
+#[derive(Debug)]
 pub enum ConfigReadinessProbe {
     None,
     ExitCode {
@@ -20,17 +28,131 @@ pub enum ConfigReadinessProbe {
     },
     Command {
         command: Vec<String>,
-
-        #[serde(default = "default_readiness_probe_period")]
         period: u32,
+        timeout: u32,
     },
     Http {
         url: String,
-
-        #[serde(default = "default_readiness_probe_http_method")]
         method: String,
-
-        #[serde(default = "default_readiness_probe_period")]
         period: u32,
+        timeout: u32,
     },
 }
+
+/*
+ * Options of all kinds of probes, the kind is chosen by the option which is set
+ */
+#[derive(Deserialize)]
+struct ConfigReadinessProbeOptions {
+    exit_code: Option<i32>,
+    delay: Option<u32>,
+    command: Option<Vec<String>>,
+    url: Option<String>,
+    method: Option<String>,
+    period: Option<u32>,
+    timeout: Option<u32>,
+}
+
+/*
+ * An empty probe (null) means no probe
+ */
+struct ConfigReadinessProbeVisitor;
+
+impl<'de> Visitor<'de> for ConfigReadinessProbeVisitor {
+    type Value = ConfigReadinessProbe;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "map with exit_code, delay, command or url")
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(ConfigReadinessProbe::None)
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let options = ConfigReadinessProbeOptions::deserialize(MapAccessDeserializer::new(map))?;
+
+        let kinds: Vec<&str> = [
+            ("exit_code", options.exit_code.is_some()),
+            ("delay", options.delay.is_some()),
+            ("command", options.command.is_some()),
+            ("url", options.url.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, is_set)| *is_set)
+        .map(|(kind, _)| kind)
+        .collect();
+
+        if kinds.len() > 1 {
+            return Err(A::Error::custom(format!(
+                "probe can have only one of exit_code, delay, command or url, got {}",
+                kinds.join(" and ")
+            )));
+        }
+
+        let periodic = options.command.is_some() || options.url.is_some();
+
+        if !periodic && (options.period.is_some() || options.timeout.is_some()) {
+            return Err(A::Error::custom(
+                "period and timeout are allowed only for command and url probes",
+            ));
+        }
+
+        if options.url.is_none() && options.method.is_some() {
+            return Err(A::Error::custom("method is allowed only for url probe"));
+        }
+
+        let period = options
+            .period
+            .unwrap_or_else(default_readiness_probe_period);
+        let timeout = options
+            .timeout
+            .unwrap_or_else(default_readiness_probe_timeout);
+
+        match options {
+            ConfigReadinessProbeOptions {
+                exit_code: Some(exit_code),
+                ..
+            } => Ok(ConfigReadinessProbe::ExitCode { exit_code }),
+            ConfigReadinessProbeOptions {
+                delay: Some(delay), ..
+            } => Ok(ConfigReadinessProbe::Delay { delay }),
+            ConfigReadinessProbeOptions {
+                command: Some(command),
+                ..
+            } => Ok(ConfigReadinessProbe::Command {
+                command,
+                period,
+                timeout,
+            }),
+            ConfigReadinessProbeOptions {
+                url: Some(url),
+                method,
+                ..
+            } => Ok(ConfigReadinessProbe::Http {
+                url,
+                method: method.unwrap_or_else(default_readiness_probe_http_method),
+                period,
+                timeout,
+            }),
+            _ => Err(A::Error::custom(
+                "unable to parse probe, expected one of exit_code, delay, command or url",
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ConfigReadinessProbe {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ConfigReadinessProbeVisitor)
+    }
+}
@@ -0,0 +1,43 @@
+use serde::Deserialize;
+
+fn default_restart_backoff_initial_delay() -> u32 {
+    1000
+}
+
+fn default_restart_backoff_multiplier() -> f64 {
+    2.0
+}
+
+fn default_restart_backoff_max_delay() -> u32 {
+    30000
+}
+
+fn default_restart_backoff_jitter() -> f64 {
+    0.1
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfigRestartBackoff {
+    #[serde(default = "default_restart_backoff_initial_delay")]
+    pub initial_delay: u32,
+
+    #[serde(default = "default_restart_backoff_multiplier")]
+    pub multiplier: f64,
+
+    #[serde(default = "default_restart_backoff_max_delay")]
+    pub max_delay: u32,
+
+    #[serde(default = "default_restart_backoff_jitter")]
+    pub jitter: f64,
+}
+
+impl Default for ConfigRestartBackoff {
+    fn default() -> Self {
+        Self {
+            initial_delay: default_restart_backoff_initial_delay(),
+            multiplier: default_restart_backoff_multiplier(),
+            max_delay: default_restart_backoff_max_delay(),
+            jitter: default_restart_backoff_jitter(),
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConfigRestartPolicy {
+    Never,
+    OnFailure,
+    Always,
+    UnlessStopped,
+}
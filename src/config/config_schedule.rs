@@ -0,0 +1,10 @@
+use crate::cron::CronExpression;
+
+#[derive(Debug)]
+pub enum ConfigSchedule {
+    // Run every N milliseconds
+    Interval(u32),
+
+    // Run at times matching the cron expression
+    Cron(CronExpression),
+}
@@ -0,0 +1,492 @@
+use std::fmt;
+
+use serde::{
+    de::{DeserializeOwned, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserializer,
+};
+use serde_yaml::Value;
+
+use crate::interpolation::interpolate;
+
+use super::config_error::ConfigProblem;
+
+/*
+ * Reported in the error on the node found by `Locator`, the error carries the location of the node
+ */
+const NODE_FOUND: &str = "node found";
+
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/*
+ * Splits a path like "apps[1].ready.exit_code" into keys and indices
+ */
+fn parse_path(path: &str) -> Vec<Segment<'_>> {
+    let mut segments: Vec<Segment> = vec![];
+
+    for part in path.split('.').filter(|x| !x.is_empty()) {
+        let (key, indices) = part.split_once('[').unwrap_or((part, ""));
+
+        if !key.is_empty() {
+            segments.push(Segment::Key(key));
+        }
+
+        for index in indices.split('[') {
+            if let Some(index) = index.strip_suffix(']').and_then(|x| x.parse().ok()) {
+                segments.push(Segment::Index(index));
+            }
+        }
+    }
+
+    segments
+}
+
+fn join_path(base: &str, path: &str) -> String {
+    match (base, path) {
+        ("", path) => path.to_string(),
+        (base, "") => base.to_string(),
+        (base, path) if path.starts_with('[') => format!("{}{}", base, path),
+        (base, path) => format!("{}.{}", base, path),
+    }
+}
+
+/*
+ * serde_yaml prefixes the message with the path of the node unless it is the root
+ */
+fn split_error_path(message: &str) -> (&str, &str) {
+    match message.split_once(": ") {
+        Some((path, rest))
+            if !path.is_empty()
+                && path
+                    .chars()
+                    .all(|x| x.is_ascii_alphanumeric() || "_-.[]".contains(x)) =>
+        {
+            (path, rest)
+        }
+        _ => ("", message),
+    }
+}
+
+/*
+ * A substituted plain scalar is resolved again, like it would be if the value were written
+ * in the config, so e.g. a number can come from a variable. Only numbers and booleans
+ * are resolved, an empty value stays an empty string rather than becoming null
+ */
+fn resolve_scalar(value: String) -> Value {
+    match serde_yaml::from_str::<Value>(&value) {
+        Ok(scalar @ (Value::Bool(_) | Value::Number(_))) => scalar,
+        _ => Value::String(value),
+    }
+}
+
+/*
+ * Walks down the document to the node at the path and fails there,
+ * serde_yaml marks the error with the location of the innermost node
+ */
+struct Locator<'a>(&'a [Segment<'a>]);
+
+/*
+ * Rejects any node, the error mentions NODE_FOUND in what is expected
+ */
+struct Found;
+
+impl<'de> Visitor<'de> for Found {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", NODE_FOUND)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Locator<'_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match self.0 {
+            [] => deserializer.deserialize_any(Found),
+            _ => deserializer.deserialize_any(self),
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for Locator<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "map or sequence")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let (key, rest) = match self.0.split_first() {
+            Some((Segment::Key(key), rest)) => (Some(*key), rest),
+            _ => (None, self.0),
+        };
+
+        // the rest of the map has to be consumed, otherwise serde_yaml reports an error
+        while let Some(current) = map.next_key::<Value>()? {
+            if key.is_some() && current.as_str() == key {
+                map.next_value_seed(Locator(rest))?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let (index, rest) = match self.0.split_first() {
+            Some((Segment::Index(index), rest)) => (Some(*index), rest),
+            _ => (None, self.0),
+        };
+
+        let mut current = 0;
+
+        loop {
+            let next = if index == Some(current) {
+                seq.next_element_seed(Locator(rest))?.map(|_| ())
+            } else {
+                seq.next_element::<IgnoredAny>()?.map(|_| ())
+            };
+
+            if next.is_none() {
+                return Ok(());
+            }
+
+            current += 1;
+        }
+    }
+}
+
+/*
+ * The text of a config file, kept to find the location of a problem by the path of the field
+ */
+#[derive(Debug)]
+pub struct ConfigSource {
+    text: String,
+}
+
+impl ConfigSource {
+    pub fn new(text: String) -> Self {
+        Self { text }
+    }
+
+    /*
+     * Parses the text into a YAML tree and substitutes environment variables,
+     * all substitution problems are reported at once
+     */
+    pub fn parse(&self) -> Result<Value, Vec<ConfigProblem>> {
+        let mut value: Value =
+            serde_yaml::from_str(&self.text).map_err(|error| vec![ConfigProblem::from(error)])?;
+
+        let mut problems: Vec<(String, String)> = vec![];
+
+        self.interpolate_tree(&mut value, "", &mut problems);
+
+        if !problems.is_empty() {
+            return Err(problems
+                .iter()
+                .map(|(path, message)| self.problem_at(path, message))
+                .collect());
+        }
+
+        Ok(value)
+    }
+
+    /*
+     * Substitutes environment variables in every string of the tree, map keys are left as is.
+     * Problems are collected with the paths of the strings
+     */
+    fn interpolate_tree(
+        &self,
+        value: &mut Value,
+        path: &str,
+        problems: &mut Vec<(String, String)>,
+    ) {
+        match value {
+            Value::String(string) => match interpolate(string) {
+                Ok(result) if result == *string => {}
+                Ok(result) if self.is_plain_scalar(path) => *value = resolve_scalar(result),
+                Ok(result) => *string = result,
+                Err(message) => problems.push((path.to_string(), message)),
+            },
+            Value::Sequence(items) => {
+                for (index, item) in items.iter_mut().enumerate() {
+                    self.interpolate_tree(item, &format!("{}[{}]", path, index), problems);
+                }
+            }
+            Value::Mapping(entries) => {
+                for (key, item) in entries.iter_mut() {
+                    let path = match key.as_str() {
+                        Some(key) => join_path(path, key),
+                        None => path.to_string(),
+                    };
+
+                    self.interpolate_tree(item, &path, problems);
+                }
+            }
+            Value::Tagged(tagged) => self.interpolate_tree(&mut tagged.value, path, problems),
+            _ => {}
+        }
+    }
+
+    /*
+     * The scalar at the path is written without quotes and is not a block scalar
+     */
+    fn is_plain_scalar(&self, path: &str) -> bool {
+        let Some((line, column)) = self.locate(path) else {
+            return false;
+        };
+
+        self.text
+            .lines()
+            .nth(line - 1)
+            .and_then(|x| x.chars().nth(column - 1))
+            .is_some_and(|x| !['\'', '"', '|', '>'].contains(&x))
+    }
+
+    fn problem_at(&self, path: &str, message: &str) -> ConfigProblem {
+        let message = match path {
+            "" => message.to_string(),
+            path => format!("{}: {}", path, message),
+        };
+
+        ConfigProblem {
+            location: self.locate(path),
+            ..ConfigProblem::new(message)
+        }
+    }
+
+    /*
+     * Deserializes the node at the path. The node is serialized back to YAML
+     * to keep the leniency of the parser, e.g. a number is accepted as a string,
+     * the location of the error is looked up in the original text
+     */
+    pub fn deserialize<T: DeserializeOwned>(
+        &self,
+        value: &Value,
+        path: &str,
+    ) -> Result<T, ConfigProblem> {
+        let text =
+            serde_yaml::to_string(value).map_err(|error| ConfigProblem::new(error.to_string()))?;
+
+        serde_yaml::from_str(&text).map_err(|error| {
+            let problem = ConfigProblem::from(error);
+            let (relative_path, message) = split_error_path(&problem.message);
+
+            self.problem_at(&join_path(path, relative_path), message)
+        })
+    }
+
+    /*
+     * Returns the line and column of the node at the path, e.g. "apps[1].ready",
+     * or of the closest parent if the node is missing
+     */
+    pub fn locate(&self, path: &str) -> Option<(usize, usize)> {
+        let segments = parse_path(path);
+
+        (0..=segments.len()).rev().find_map(|depth| {
+            let error = Locator(&segments[..depth])
+                .deserialize(serde_yaml::Deserializer::from_str(&self.text))
+                .err()?;
+
+            if !error.to_string().contains(NODE_FOUND) {
+                return None;
+            }
+
+            error
+                .location()
+                .map(|location| (location.line(), location.column()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    const TEXT: &str = "log_level: info
+apps:
+  - name: a
+    command: [sleep, 1]
+  - name: b
+    command:
+      - sleep
+      - 2
+    ready: {exit_code: 0}
+";
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct App {
+        name: String,
+        command: Vec<String>,
+        timeout: Option<u32>,
+    }
+
+    #[test]
+    fn parses_paths() {
+        let segments = parse_path("apps[1].stop_sequence[0].signal");
+
+        assert!(matches!(
+            segments.as_slice(),
+            [
+                Segment::Key("apps"),
+                Segment::Index(1),
+                Segment::Key("stop_sequence"),
+                Segment::Index(0),
+                Segment::Key("signal")
+            ]
+        ));
+        assert_eq!(join_path("apps[1]", "ready"), "apps[1].ready");
+        assert_eq!(join_path("apps", "[1]"), "apps[1]");
+        assert_eq!(join_path("", "apps"), "apps");
+    }
+
+    #[test]
+    fn locates_nodes() {
+        let source = ConfigSource::new(TEXT.to_string());
+
+        assert_eq!(source.locate("log_level"), Some((1, 12)));
+        assert_eq!(source.locate("apps[0]"), Some((3, 5)));
+        assert_eq!(source.locate("apps[0].command[1]"), Some((4, 22)));
+        assert_eq!(source.locate("apps[1].command[1]"), Some((8, 9)));
+        assert_eq!(source.locate("apps[1].ready.exit_code"), Some((9, 24)));
+    }
+
+    #[test]
+    fn locates_closest_parent_of_missing_nodes() {
+        let source = ConfigSource::new(TEXT.to_string());
+
+        assert_eq!(source.locate("apps[1].stop_sequence[0]"), Some((5, 5)));
+        assert_eq!(source.locate("apps[5].name"), source.locate("apps"));
+        assert_eq!(source.locate("log_level.x"), Some((1, 12)));
+    }
+
+    #[test]
+    fn reports_deserialization_errors_with_path_and_location() {
+        let source = ConfigSource::new(TEXT.replace("command: [sleep, 1]", "timeout: x"));
+        let value = source.parse().unwrap();
+
+        let problem = source
+            .deserialize::<App>(&value["apps"][0], "apps[0]")
+            .unwrap_err();
+
+        assert_eq!(
+            problem.message,
+            "apps[0].timeout: invalid type: string \"x\", expected u32"
+        );
+        assert_eq!(problem.location, Some((4, 14)));
+    }
+
+    #[test]
+    fn accepts_numbers_as_strings() {
+        let source = ConfigSource::new(TEXT.to_string());
+        let value = source.parse().unwrap();
+
+        let app: App = source.deserialize(&value["apps"][1], "apps[1]").unwrap();
+
+        assert_eq!(app.command, vec!["sleep", "2"]);
+    }
+
+    #[test]
+    fn interpolates_string_values_only() {
+        std::env::set_var("MAESTRO_TEST_NAME", "server");
+
+        let source = ConfigSource::new(
+            "${MAESTRO_TEST_NAME}: ${MAESTRO_TEST_NAME}\nlist: [\"${MAESTRO_TEST_NAME}\", 5]\n"
+                .to_string(),
+        );
+        let value = source.parse().unwrap();
+
+        assert_eq!(value["${MAESTRO_TEST_NAME}"], "server");
+        assert_eq!(value["list"][0], "server");
+        assert_eq!(value["list"][1], 5);
+    }
+
+    #[test]
+    fn resolves_substituted_plain_scalars() {
+        std::env::set_var("MAESTRO_TEST_NUMBER", "5000");
+        std::env::set_var("MAESTRO_TEST_BOOL", "true");
+        std::env::set_var("MAESTRO_TEST_TEXT", "a: b");
+
+        let source = ConfigSource::new(
+            r#"number: ${MAESTRO_TEST_NUMBER}
+quoted: "${MAESTRO_TEST_NUMBER}"
+single: '${MAESTRO_TEST_NUMBER}'
+bool: ${MAESTRO_TEST_BOOL}
+text: ${MAESTRO_TEST_TEXT}
+empty: ${MAESTRO_TEST_MISSING_VARIABLE}
+list:
+  - ${MAESTRO_TEST_NUMBER}
+"#
+            .to_string(),
+        );
+        let value = source.parse().unwrap();
+
+        assert_eq!(value["number"], 5000);
+        assert_eq!(value["quoted"], "5000");
+        assert_eq!(value["single"], "5000");
+        assert_eq!(value["bool"], true);
+        assert_eq!(value["text"], "a: b");
+        assert_eq!(value["empty"], "");
+        assert_eq!(value["list"][0], 5000);
+    }
+
+    #[test]
+    fn accepts_substituted_numbers_as_strings() {
+        std::env::set_var("MAESTRO_TEST_ARGUMENT", "2");
+
+        let source = ConfigSource::new(
+            "name: a\ncommand:\n  - sleep\n  - ${MAESTRO_TEST_ARGUMENT}\ntimeout: ${MAESTRO_TEST_ARGUMENT}\n"
+                .to_string(),
+        );
+        let value = source.parse().unwrap();
+
+        let app: App = source.deserialize(&value, "").unwrap();
+
+        assert_eq!(app.command, vec!["sleep", "2"]);
+        assert_eq!(app.timeout, Some(2));
+    }
+
+    #[test]
+    fn reports_all_interpolation_problems() {
+        std::env::remove_var("MAESTRO_TEST_REQUIRED");
+
+        let source =
+            ConfigSource::new("a: ${MAESTRO_TEST_REQUIRED:?}\nb:\n  - ok\n  - ${}\n".to_string());
+        let problems = source.parse().unwrap_err();
+
+        let problems: Vec<(String, Option<(usize, usize)>)> = problems
+            .into_iter()
+            .map(|problem| (problem.message, problem.location))
+            .collect();
+
+        assert_eq!(
+            problems,
+            vec![
+                (
+                    "a: required variable \"MAESTRO_TEST_REQUIRED\" is not set".to_string(),
+                    Some((1, 4))
+                ),
+                (
+                    "b[1]: invalid variable reference \"${}\"".to_string(),
+                    Some((4, 5))
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use serde::Deserialize;
+
+fn default_start_limit_burst() -> u32 {
+    5
+}
+
+fn default_start_limit_interval() -> u32 {
+    60000
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfigStartLimit {
+    #[serde(default = "default_start_limit_burst")]
+    pub burst: u32,
+
+    #[serde(default = "default_start_limit_interval")]
+    pub interval: u32,
+}
+
+impl Default for ConfigStartLimit {
+    fn default() -> Self {
+        Self {
+            burst: default_start_limit_burst(),
+            interval: default_start_limit_interval(),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::fmt;
+
+use serde::{
+    de::{value::MapAccessDeserializer, Error, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+use super::config_app::deserialize_optional_signal;
+
+#[derive(Debug)]
+pub enum ConfigStopStep {
+    Signal {
+        signal: i32,
+        wait: Option<u32>,
+    },
+    Command {
+        command: Vec<String>,
+        wait: Option<u32>,
+    },
+}
+
+/*
+ * Options of all kinds of steps, the kind is chosen by the option which is set
+ */
+#[derive(Deserialize)]
+struct ConfigStopStepOptions {
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_optional_signal")]
+    signal: Option<i32>,
+
+    command: Option<Vec<String>>,
+
+    wait: Option<u32>,
+}
+
+struct ConfigStopStepVisitor;
+
+impl<'de> Visitor<'de> for ConfigStopStepVisitor {
+    type Value = ConfigStopStep;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "map with signal or command")
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let ConfigStopStepOptions {
+            signal,
+            command,
+            wait,
+        } = ConfigStopStepOptions::deserialize(MapAccessDeserializer::new(map))?;
+
+        match (signal, command) {
+            (Some(signal), None) => Ok(ConfigStopStep::Signal { signal, wait }),
+            (None, Some(command)) => Ok(ConfigStopStep::Command { command, wait }),
+            (Some(_), Some(_)) => Err(A::Error::custom(
+                "stop step can have either signal or command, not both",
+            )),
+            (None, None) => Err(A::Error::custom(
+                "unable to parse stop step, expected signal or command",
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ConfigStopStep {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(ConfigStopStepVisitor)
+    }
+}
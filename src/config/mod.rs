@@ -1,7 +1,36 @@
+#[allow(clippy::module_inception)]
 mod config;
 mod config_app;
+mod config_app_type;
+mod config_dependency;
+mod config_dependency_condition;
+mod config_error;
+mod config_failure_policy;
+mod config_fragment;
+mod config_liveness_action;
+mod config_liveness_probe;
+mod config_overlap_policy;
 mod config_readiness_probe;
+mod config_restart_backoff;
+mod config_restart_policy;
+mod config_schedule;
+mod config_source;
+mod config_start_limit;
+mod config_stop_step;
 
 pub use config::Config;
 pub use config_app::ConfigApp;
+pub use config_app_type::ConfigAppType;
+pub use config_dependency::ConfigDependency;
+pub use config_dependency_condition::ConfigDependencyCondition;
+pub use config_error::{ConfigError, ConfigProblem, CONFIG_ERROR_EXIT_CODE};
+pub use config_failure_policy::ConfigFailurePolicy;
+pub use config_liveness_action::ConfigLivenessAction;
+pub use config_liveness_probe::ConfigLivenessProbe;
+pub use config_overlap_policy::ConfigOverlapPolicy;
 pub use config_readiness_probe::ConfigReadinessProbe;
+pub use config_restart_backoff::ConfigRestartBackoff;
+pub use config_restart_policy::ConfigRestartPolicy;
+pub use config_schedule::ConfigSchedule;
+pub use config_start_limit::ConfigStartLimit;
+pub use config_stop_step::ConfigStopStep;
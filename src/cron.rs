@@ -0,0 +1,290 @@
+use std::{mem::MaybeUninit, time::Duration};
+
+const FIELDS: [(&str, u32, u32); 5] = [
+    ("minute", 0, 59),
+    ("hour", 0, 23),
+    ("day of month", 1, 31),
+    ("month", 1, 12),
+    ("day of week", 0, 7),
+];
+
+/*
+ * A classic 5-field cron expression: minute, hour, day of month, month and day of week.
+ * Every field is stored as a bitset of the allowed values
+ */
+#[derive(Debug, Clone)]
+pub struct CronExpression {
+    source: String,
+    fields: [u64; 5],
+
+    // Day of month and day of week are OR-ed if both are restricted
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+/*
+ * The bitset with all values from `min` to `max` (inclusive)
+ */
+fn range_bits(min: u32, max: u32) -> u64 {
+    (min..=max).fold(0, |bits, value| bits | 1 << value)
+}
+
+fn parse_value(value: &str, name: &str, min: u32, max: u32) -> Result<u32, String> {
+    let number = value
+        .parse::<u32>()
+        .map_err(|_| format!("invalid {} value \"{}\"", name, value))?;
+
+    if number < min || number > max {
+        return Err(format!(
+            "{} value {} is out of range {}-{}",
+            name, number, min, max
+        ));
+    }
+
+    Ok(number)
+}
+
+fn parse_field(field: &str, name: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut bits = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, parse_value(step, name, 1, max)?),
+            None => (part, 1),
+        };
+
+        let (from, to) = if range == "*" {
+            (min, max)
+        } else {
+            match range.split_once('-') {
+                Some((from, to)) => (
+                    parse_value(from, name, min, max)?,
+                    parse_value(to, name, min, max)?,
+                ),
+                None => {
+                    let value = parse_value(range, name, min, max)?;
+
+                    // "5/15" means "starting from 5 every 15"
+                    if part.contains('/') {
+                        (value, max)
+                    } else {
+                        (value, value)
+                    }
+                }
+            }
+        };
+
+        if from > to {
+            return Err(format!("invalid {} range \"{}\"", name, range));
+        }
+
+        for value in (from..=to).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+
+    Ok(bits)
+}
+
+impl CronExpression {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expanded = match expression.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            value => value,
+        };
+
+        let parts: Vec<&str> = expanded.split_whitespace().collect();
+
+        if parts.len() != FIELDS.len() {
+            return Err(format!(
+                "cron expression \"{}\" must have {} fields",
+                expression,
+                FIELDS.len()
+            ));
+        }
+
+        let mut fields = [0u64; 5];
+
+        for (index, (name, min, max)) in FIELDS.iter().enumerate() {
+            fields[index] = parse_field(parts[index], name, *min, *max)?;
+        }
+
+        // both 0 and 7 mean Sunday
+        if fields[4] & (1 << 7) != 0 {
+            fields[4] |= 1;
+        }
+
+        /*
+         * A field is restricted if it does not allow every day, however it is written
+         * (e.g. "1-31" is the same as "*", and so is "0-6" for the day of week)
+         */
+        let dom_bits = range_bits(1, 31);
+        let dow_bits = range_bits(0, 6);
+
+        Ok(Self {
+            source: expression.to_string(),
+            fields,
+            dom_restricted: fields[2] & dom_bits != dom_bits,
+            dow_restricted: fields[4] & dow_bits != dow_bits,
+        })
+    }
+
+    /*
+     * Checks if the expression matches the minute which contains `time` (since UNIX epoch),
+     * the time is converted to the local time zone
+     */
+    pub fn matches(&self, time: Duration) -> bool {
+        let seconds = time.as_secs() as libc::time_t;
+        let mut tm = MaybeUninit::<libc::tm>::uninit();
+
+        let tm = unsafe {
+            if libc::localtime_r(&seconds, tm.as_mut_ptr()).is_null() {
+                return false;
+            }
+
+            tm.assume_init()
+        };
+
+        let is_set = |index: usize, value: i32| self.fields[index] & (1 << value) != 0;
+
+        let dom = is_set(2, tm.tm_mday);
+        let dow = is_set(4, tm.tm_wday);
+
+        let day = match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            _ => dom && dow,
+        };
+
+        is_set(0, tm.tm_min) && is_set(1, tm.tm_hour) && is_set(3, tm.tm_mon + 1) && day
+    }
+}
+
+impl std::fmt::Display for CronExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits(values: &[u32]) -> u64 {
+        values.iter().fold(0, |bits, value| bits | 1 << value)
+    }
+
+    #[test]
+    fn parses_wildcards() {
+        let expression = CronExpression::parse("* * * * *").unwrap();
+
+        assert_eq!(expression.fields[0], range_bits(0, 59));
+        assert_eq!(expression.fields[1], range_bits(0, 23));
+        assert_eq!(expression.fields[2], range_bits(1, 31));
+        assert_eq!(expression.fields[3], range_bits(1, 12));
+        assert_eq!(expression.fields[4], range_bits(0, 7));
+        assert!(!expression.dom_restricted);
+        assert!(!expression.dow_restricted);
+    }
+
+    #[test]
+    fn parses_values_lists_and_ranges() {
+        let expression = CronExpression::parse("5 1,13 10-12 1-3,6 *").unwrap();
+
+        assert_eq!(expression.fields[0], bits(&[5]));
+        assert_eq!(expression.fields[1], bits(&[1, 13]));
+        assert_eq!(expression.fields[2], bits(&[10, 11, 12]));
+        assert_eq!(expression.fields[3], bits(&[1, 2, 3, 6]));
+    }
+
+    #[test]
+    fn parses_steps() {
+        let expression = CronExpression::parse("*/15 0-6/2 5/10 * *").unwrap();
+
+        assert_eq!(expression.fields[0], bits(&[0, 15, 30, 45]));
+        assert_eq!(expression.fields[1], bits(&[0, 2, 4, 6]));
+        assert_eq!(expression.fields[2], bits(&[5, 15, 25]));
+    }
+
+    #[test]
+    fn treats_seven_as_sunday() {
+        let expression = CronExpression::parse("0 0 * * 7").unwrap();
+
+        assert_eq!(expression.fields[4], bits(&[0, 7]));
+    }
+
+    #[test]
+    fn expands_macros() {
+        let daily = CronExpression::parse("@daily").unwrap();
+        let weekly = CronExpression::parse("@weekly").unwrap();
+
+        assert_eq!(daily.fields[0], bits(&[0]));
+        assert_eq!(daily.fields[1], bits(&[0]));
+        assert!(!daily.dom_restricted && !daily.dow_restricted);
+        assert_eq!(weekly.fields[4], bits(&[0]));
+        assert!(weekly.dow_restricted);
+
+        // the original expression is kept for messages
+        assert_eq!(weekly.to_string(), "@weekly");
+    }
+
+    #[test]
+    fn detects_restricted_days_from_values() {
+        let full = CronExpression::parse("0 0 1-31 * 0-6").unwrap();
+        let both = CronExpression::parse("0 0 1 * 1").unwrap();
+        let stepped = CronExpression::parse("0 0 */2 * */1").unwrap();
+
+        assert!(!full.dom_restricted && !full.dow_restricted);
+        assert!(both.dom_restricted && both.dow_restricted);
+        assert!(stepped.dom_restricted && !stepped.dow_restricted);
+    }
+
+    #[test]
+    fn matches_every_minute() {
+        let expression = CronExpression::parse("* * * * *").unwrap();
+
+        assert!(expression.matches(Duration::from_secs(0)));
+        assert!(expression.matches(Duration::from_secs(1_700_000_000)));
+    }
+
+    #[test]
+    fn rejects_wrong_number_of_fields() {
+        assert_eq!(
+            CronExpression::parse("* * * *").unwrap_err(),
+            "cron expression \"* * * *\" must have 5 fields"
+        );
+        assert!(CronExpression::parse("@reboot").is_err());
+        assert!(CronExpression::parse("").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert_eq!(
+            CronExpression::parse("60 * * * *").unwrap_err(),
+            "minute value 60 is out of range 0-59"
+        );
+        assert_eq!(
+            CronExpression::parse("* * 0 * *").unwrap_err(),
+            "day of month value 0 is out of range 1-31"
+        );
+        assert_eq!(
+            CronExpression::parse("* * * 13 *").unwrap_err(),
+            "month value 13 is out of range 1-12"
+        );
+        assert_eq!(
+            CronExpression::parse("* * * * mon").unwrap_err(),
+            "invalid day of week value \"mon\""
+        );
+        assert_eq!(
+            CronExpression::parse("*/0 * * * *").unwrap_err(),
+            "minute value 0 is out of range 1-59"
+        );
+        assert_eq!(
+            CronExpression::parse("* 10-2 * * *").unwrap_err(),
+            "invalid hour range \"10-2\""
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use std::sync::{
+    mpsc::{self, Receiver, Sender},
+    OnceLock,
+};
+
+/*
+ * Everything the main loop is waiting for.
+ * `Wake` means that something might have changed (a child has exited, a check has completed)
+ * and the apps must be updated
+ */
+#[derive(Debug)]
+pub enum Event {
+    Signal(i32),
+    Wake,
+}
+
+static SENDER: OnceLock<Sender<Event>> = OnceLock::new();
+
+/*
+ * Creates the channel, must be called once before any event is sent
+ */
+pub fn init() -> Receiver<Event> {
+    let (sender, receiver) = mpsc::channel();
+
+    SENDER
+        .set(sender)
+        .expect("events channel is already initialized");
+
+    receiver
+}
+
+pub fn send(event: Event) {
+    if let Some(sender) = SENDER.get() {
+        sender.send(event).ok();
+    }
+}
+
+pub fn wake() {
+    send(Event::Wake);
+}
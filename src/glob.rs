@@ -0,0 +1,208 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+fn has_wildcards(value: &str) -> bool {
+    value.contains(['*', '?'])
+}
+
+/*
+ * Matches a file name against a pattern, where "*" matches any sequence of characters
+ * and "?" matches exactly one character. On a mismatch the last "*" is retried
+ * with one more character, so the time is linear for each "*" at most
+ */
+fn matches(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+
+    // The position of the last "*" in the pattern and the name position it is retried from
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&expected) if expected == '?' || expected == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|x| *x == '*')
+}
+
+/*
+ * Returns the files matching the pattern sorted by name. Wildcards are supported
+ * only in the file name, e.g. "conf.d/app-?.yml". A pattern without wildcards
+ * is returned as is, even if the file does not exist
+ */
+pub fn expand(pattern: &Path) -> Result<Vec<PathBuf>, String> {
+    let dir = pattern.parent().unwrap_or(Path::new("."));
+
+    if dir.to_str().is_some_and(has_wildcards) {
+        return Err(format!(
+            "invalid pattern \"{}\", wildcards are supported only in file names",
+            pattern.display()
+        ));
+    }
+
+    let file_pattern = match pattern.file_name().and_then(|x| x.to_str()) {
+        Some(value) if has_wildcards(value) => value,
+        _ => return Ok(vec![pattern.to_path_buf()]),
+    };
+
+    /*
+     * A missing directory matches nothing, like any other pattern that matches nothing
+     */
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) if !dir.exists() => return Ok(vec![]),
+        Err(err) => return Err(format!("unable to read {}, {}", dir.display(), err)),
+    };
+
+    let file_pattern: Vec<char> = file_pattern.chars().collect();
+    let mut paths: Vec<PathBuf> = vec![];
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        let is_match = entry.file_name().to_str().is_some_and(|name| {
+            // hidden files are matched only explicitly, like in shells
+            (!name.starts_with('.') || file_pattern.first() == Some(&'.'))
+                && matches(&file_pattern, &name.chars().collect::<Vec<char>>())
+        });
+
+        if is_match && path.is_file() {
+            paths.push(path);
+        }
+    }
+
+    paths.sort();
+
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_match(pattern: &str, name: &str) -> bool {
+        matches(
+            &pattern.chars().collect::<Vec<char>>(),
+            &name.chars().collect::<Vec<char>>(),
+        )
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("maestro-glob-{}-{}", name, std::process::id()));
+
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn matches_literals() {
+        assert!(is_match("app.yml", "app.yml"));
+        assert!(!is_match("app.yml", "app.yaml"));
+        assert!(!is_match("app.yml", "app.yml2"));
+        assert!(is_match("", ""));
+        assert!(!is_match("", "a"));
+    }
+
+    #[test]
+    fn matches_question_mark() {
+        assert!(is_match("app-?.yml", "app-1.yml"));
+        assert!(!is_match("app-?.yml", "app-.yml"));
+        assert!(!is_match("app-?.yml", "app-12.yml"));
+        assert!(is_match("??", "ab"));
+    }
+
+    #[test]
+    fn matches_star() {
+        assert!(is_match("*", ""));
+        assert!(is_match("*", "anything"));
+        assert!(is_match("*.yml", ".yml"));
+        assert!(is_match("*.yml", "app.yml"));
+        assert!(!is_match("*.yml", "app.yaml"));
+        assert!(is_match("app*", "app"));
+        assert!(is_match("a*b*c", "aXbYbZc"));
+        assert!(!is_match("a*b*c", "aXbYbZ"));
+        assert!(is_match("**.yml", "a.yml"));
+        assert!(is_match("*?", "a"));
+        assert!(!is_match("*?", ""));
+    }
+
+    #[test]
+    fn retries_star_after_partial_match() {
+        assert!(is_match("*.yml", "a.ym.yml"));
+        assert!(is_match("*ab", "aab"));
+        assert!(!is_match("*ab", "aba"));
+    }
+
+    #[test]
+    fn matches_long_names_with_many_stars() {
+        let name = "a".repeat(100);
+
+        assert!(is_match(&"*a".repeat(20), &name));
+        assert!(!is_match(&format!("{}b", "*a".repeat(20)), &name));
+    }
+
+    #[test]
+    fn returns_pattern_without_wildcards_as_is() {
+        let path = Path::new("/nonexistent/app.yml");
+
+        assert_eq!(expand(path).unwrap(), vec![path.to_path_buf()]);
+    }
+
+    #[test]
+    fn rejects_wildcards_in_directories() {
+        assert_eq!(
+            expand(Path::new("/etc/*/app.yml")).unwrap_err(),
+            "invalid pattern \"/etc/*/app.yml\", wildcards are supported only in file names"
+        );
+    }
+
+    #[test]
+    fn matches_nothing_in_missing_directory() {
+        assert!(expand(Path::new("/nonexistent/*.yml")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn expands_to_sorted_files() {
+        let dir = temp_dir("expand");
+
+        for name in ["b.yml", "a.yml", "c.yaml", ".hidden.yml"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        fs::create_dir(dir.join("dir.yml")).unwrap();
+
+        let names = |pattern: &str| -> Vec<String> {
+            expand(&dir.join(pattern))
+                .unwrap()
+                .iter()
+                .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+                .collect()
+        };
+
+        assert_eq!(names("*.yml"), vec!["a.yml", "b.yml"]);
+        assert_eq!(names("?.y*"), vec!["a.yml", "b.yml", "c.yaml"]);
+        assert_eq!(names(".*"), vec![".hidden.yml"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
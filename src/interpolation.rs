@@ -0,0 +1,182 @@
+use std::env;
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|x| x.is_ascii_alphanumeric() || x == '_')
+}
+
+/*
+ * Resolves the expression between "${" and "}": "VAR", "VAR:-default" or "VAR:?error"
+ */
+fn resolve(expression: &str) -> Result<String, String> {
+    let (name, modifier) = match expression.split_once(':') {
+        Some((name, modifier)) => (name, Some(modifier)),
+        None => (expression, None),
+    };
+
+    if !is_valid_name(name) || modifier.is_some_and(|x| !x.starts_with(['-', '?'])) {
+        return Err(format!(
+            "invalid variable reference \"${{{}}}\"",
+            expression
+        ));
+    }
+
+    // empty variables are treated as unset by both modifiers
+    let value = env::var(name).ok().filter(|x| !x.is_empty());
+
+    match (value, modifier) {
+        (Some(value), _) => Ok(value),
+        (None, None) => Ok(String::new()),
+        (None, Some(modifier)) => match modifier.split_at(1) {
+            ("-", default) => Ok(default.to_string()),
+            (_, "") => Err(format!("required variable \"{}\" is not set", name)),
+            (_, error) => Err(format!(
+                "required variable \"{}\" is not set, {}",
+                name, error
+            )),
+        },
+    }
+}
+
+/*
+ * Substitutes environment variables: "${VAR}" (empty if unset), "${VAR:-default}"
+ * and "${VAR:?error}". "$${" is a literal "${", any other "$" (including "$$")
+ * is left as is, so "$HOME" or "$$" still reach the shell of the app
+ */
+pub fn interpolate(value: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(index) = rest.find('$') {
+        result.push_str(&rest[..index]);
+        rest = &rest[index..];
+
+        if let Some(after) = rest.strip_prefix("$${") {
+            result.push_str("${");
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("${") {
+            let end = after
+                .find('}')
+                .ok_or(format!("unterminated variable reference in \"{}\"", value))?;
+
+            result.push_str(&resolve(&after[..end])?);
+            rest = &after[end + 1..];
+        } else {
+            result.push('$');
+            rest = &rest[1..];
+        }
+    }
+
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaves_plain_strings_as_is() {
+        assert_eq!(interpolate("").unwrap(), "");
+        assert_eq!(
+            interpolate("./server --port 80").unwrap(),
+            "./server --port 80"
+        );
+    }
+
+    #[test]
+    fn substitutes_variables() {
+        env::set_var("MAESTRO_TEST_HOST", "localhost");
+        env::set_var("MAESTRO_TEST_PORT", "3000");
+
+        assert_eq!(
+            interpolate("http://${MAESTRO_TEST_HOST}:${MAESTRO_TEST_PORT}/").unwrap(),
+            "http://localhost:3000/"
+        );
+        assert_eq!(interpolate("${MAESTRO_TEST_PORT}").unwrap(), "3000");
+    }
+
+    #[test]
+    fn substitutes_missing_variables_with_empty_string() {
+        env::remove_var("MAESTRO_TEST_MISSING");
+
+        assert_eq!(interpolate("a${MAESTRO_TEST_MISSING}b").unwrap(), "ab");
+    }
+
+    #[test]
+    fn substitutes_defaults() {
+        env::remove_var("MAESTRO_TEST_UNSET");
+        env::set_var("MAESTRO_TEST_EMPTY", "");
+        env::set_var("MAESTRO_TEST_SET", "value");
+
+        assert_eq!(interpolate("${MAESTRO_TEST_UNSET:-80}").unwrap(), "80");
+        assert_eq!(interpolate("${MAESTRO_TEST_EMPTY:-80}").unwrap(), "80");
+        assert_eq!(interpolate("${MAESTRO_TEST_SET:-80}").unwrap(), "value");
+        assert_eq!(interpolate("${MAESTRO_TEST_UNSET:-}").unwrap(), "");
+        assert_eq!(
+            interpolate("${MAESTRO_TEST_UNSET:-a:b-c}").unwrap(),
+            "a:b-c"
+        );
+    }
+
+    #[test]
+    fn requires_variables() {
+        env::remove_var("MAESTRO_TEST_REQUIRED");
+        env::set_var("MAESTRO_TEST_PRESENT", "x");
+
+        assert_eq!(
+            interpolate("${MAESTRO_TEST_REQUIRED:?}").unwrap_err(),
+            "required variable \"MAESTRO_TEST_REQUIRED\" is not set"
+        );
+        assert_eq!(
+            interpolate("${MAESTRO_TEST_REQUIRED:?set the port}").unwrap_err(),
+            "required variable \"MAESTRO_TEST_REQUIRED\" is not set, set the port"
+        );
+        assert_eq!(interpolate("${MAESTRO_TEST_PRESENT:?}").unwrap(), "x");
+    }
+
+    #[test]
+    fn leaves_shell_syntax_as_is() {
+        assert_eq!(
+            interpolate("echo $HOME $$ $1 $").unwrap(),
+            "echo $HOME $$ $1 $"
+        );
+        assert_eq!(interpolate("kill $$").unwrap(), "kill $$");
+        assert_eq!(interpolate("price: 5$").unwrap(), "price: 5$");
+    }
+
+    #[test]
+    fn escapes_references() {
+        assert_eq!(interpolate("$${HOME}").unwrap(), "${HOME}");
+        assert_eq!(interpolate("$${HOME:-x} $$").unwrap(), "${HOME:-x} $$");
+    }
+
+    #[test]
+    fn rejects_invalid_references() {
+        assert_eq!(
+            interpolate("${}").unwrap_err(),
+            "invalid variable reference \"${}\""
+        );
+        assert_eq!(
+            interpolate("${1VAR}").unwrap_err(),
+            "invalid variable reference \"${1VAR}\""
+        );
+        assert_eq!(
+            interpolate("${VAR-default}").unwrap_err(),
+            "invalid variable reference \"${VAR-default}\""
+        );
+        assert_eq!(
+            interpolate("${VAR:+x}").unwrap_err(),
+            "invalid variable reference \"${VAR:+x}\""
+        );
+        assert_eq!(
+            interpolate("a ${VAR").unwrap_err(),
+            "unterminated variable reference in \"a ${VAR\""
+        );
+    }
+}
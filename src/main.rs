@@ -1,55 +1,201 @@
 mod app;
+mod check;
+mod cli;
 mod config;
+mod cron;
+mod events;
 mod fs;
+mod glob;
+mod interpolation;
 mod logger;
 mod pid;
+mod process;
 mod readiness_probe;
+mod reaper;
+mod signal;
 mod user;
 mod utils;
 
 use crate::{
-    app::{App, AppsMap},
+    app::{App, AppDependency, AppFailurePolicy, AppsMap},
+    cli::{Cli, CliCommand},
+    events::Event,
     logger::init_logger,
     pid::init_pid,
+    utils::get_now,
 };
-use app::AppStatus;
-use config::Config;
+use app::{AppStatus, AppType};
+use config::{Config, CONFIG_ERROR_EXIT_CODE};
 use signal_hook::{
-    consts::{SIGINT, SIGTERM},
+    consts::{SIGCHLD, SIGINT, SIGQUIT, SIGTERM},
     iterator::Signals,
 };
-use std::{thread, time};
-
-const POLL_PERIOD: u64 = 100;
+use std::{collections::HashSet, thread};
 
+#[derive(PartialEq)]
 enum MainState {
     Running,
     Stopping,
 }
 
 fn main() {
-    let config = Config::new().validate();
+    let cli = Cli::parse();
+
+    if cli.command == CliCommand::Check {
+        std::process::exit(check::run(cli.config));
+    }
+
+    let mut config = match Config::new(cli.config).and_then(|config| config.validate()) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(CONFIG_ERROR_EXIT_CODE);
+        }
+    };
+
+    if let Some(log_level) = cli.log_level {
+        config.log_level = log_level;
+    }
 
     init_logger(config.log_level.to_owned());
     init_pid(config.pid.to_owned());
 
     log::debug!("loaded config {:#?}", config);
 
-    let mut signals = Signals::new([SIGTERM, SIGINT]).unwrap();
+    /*
+     * As PID 1 (e.g. in Docker) maestro inherits every orphaned process in the container
+     */
+    let init_mode = config.init.unwrap_or(std::process::id() == 1);
+
+    if init_mode {
+        reaper::init();
+    }
+
+    let mut handled_signals = vec![SIGTERM, SIGINT, SIGQUIT];
+
+    for app in config.apps.iter() {
+        handled_signals.extend(app.forward_signals.iter());
+    }
+
+    handled_signals.extend(config.forward_signals.iter());
+    handled_signals.sort();
+    handled_signals.dedup();
+
+    let events = events::init();
+    let mut signals = Signals::new(handled_signals).unwrap();
+
+    /*
+     * SIGCHLD wakes the main loop up when any child exits
+     */
+    signals.add_signal(SIGCHLD).unwrap();
+
+    thread::spawn(move || {
+        for signal in signals.forever() {
+            match signal {
+                SIGCHLD => events::wake(),
+                _ => events::send(Event::Signal(signal)),
+            }
+        }
+    });
+
+    let mut received_signals: Vec<i32> = vec![];
     let mut state = MainState::Running;
-    let mut stop_flag = false;
     let mut apps_map = AppsMap::new();
 
-    for config_app in config.apps {
-        let deps = config_app.depends_on.to_owned();
+    /*
+     * Failures that have already been handled according to the app's failure policy
+     */
+    let mut handled_failures: HashSet<String> = HashSet::new();
+
+    /*
+     * Apps that are stopped (and must not be started) because an app they depend on has failed
+     */
+    let mut held: HashSet<String> = HashSet::new();
+
+    /*
+     * Failed apps that are waiting for their dependents to stop before being restarted
+     */
+    let mut pending_restarts: HashSet<String> = HashSet::new();
+
+    for mut config_app in config.apps {
+        let deps = config_app
+            .depends_on
+            .iter()
+            .cloned()
+            .map(AppDependency::from)
+            .collect();
+
+        config_app.stop_timeout.get_or_insert(config.stop_timeout);
+
+        /*
+         * Globally forwarded signals are relayed to every app
+         */
+        for signal in config.forward_signals.iter() {
+            if !config_app.forward_signals.contains(signal) {
+                config_app.forward_signals.push(*signal);
+            }
+        }
 
         apps_map.add(App::from(config_app), deps);
     }
 
+    for app_rc in apps_map.list() {
+        let name = app_rc.borrow().get_name();
+
+        if let Some((chain, reason)) = apps_map.find_unsatisfiable_chain(&name) {
+            log::warn!(
+                "app \"{}\" can never be started: {} ({})",
+                name,
+                chain.join(" -> "),
+                reason
+            );
+        }
+    }
+
+    /*
+     * Apps that will never be started because a dependency has failed, they are reported once
+     */
+    let mut blocked: HashSet<String> = HashSet::new();
+
     loop {
-        for signal in signals.pending() {
+        let iteration_started_at = get_now();
+        let snapshot_before = get_snapshot(&apps_map, &state, &held);
+        let mut force_kill = false;
+
+        for signal in received_signals.drain(..) {
             log::info!("received signal {:?}", signal);
-            state = MainState::Stopping;
+
+            match signal {
+                SIGTERM | SIGINT if matches!(state, MainState::Running) => {
+                    state = MainState::Stopping;
+                }
+                /*
+                 * The second shutdown signal (or SIGQUIT) means the operator
+                 * does not want to wait for the graceful shutdown
+                 */
+                SIGTERM | SIGINT | SIGQUIT => {
+                    state = MainState::Stopping;
+                    force_kill = true;
+                }
+                _ => {
+                    for app_rc in apps_map.list() {
+                        app_rc.borrow().forward_signal(signal);
+                    }
+                }
+            }
+        }
+
+        if force_kill {
+            let killed: Vec<String> = apps_map
+                .list()
+                .filter_map(|app_rc| {
+                    let mut app = app_rc.borrow_mut();
+
+                    app.force_kill().then(|| app.get_name())
+                })
+                .collect();
+
+            log::warn!("shutting down immediately, force-killed apps: {:?}", killed);
         }
 
         for app_rc in apps_map.list() {
@@ -59,72 +205,236 @@ fn main() {
 
             match state {
                 MainState::Running => {
-                    if app.get_status() == AppStatus::Init {
-                        let ready = apps_map
-                            .get_dependencies_for(&app.get_name())
-                            .iter()
-                            .all(|app_name| apps_map.get(app_name).unwrap().borrow().is_ready());
+                    let name = app.get_name();
 
-                        if ready {
-                            app.run();
+                    if held.contains(&name) {
+                        /*
+                         * An app it depends on has failed, the app must be stopped
+                         * after its own dependents and must not be started
+                         */
+                        if app.get_status() == AppStatus::Backoff {
+                            app.cancel_restart();
+                        }
+
+                        if app.get_status() == AppStatus::Running
+                            && app.get_type() != AppType::Oneshot
+                            && apps_map.dependents_stopped(&name)
+                        {
+                            app.stop();
                         }
+
+                        continue;
                     }
 
-                    if app.get_status() == AppStatus::Stopped
-                        && app.get_exit_code().unwrap_or(1) != 0
-                    {
+                    if app.is_scheduled() {
                         /*
-                         * The app failed, so system operation is not guaranteed
+                         * Scheduled runs have no effect on the stack:
+                         * they are never restarted and their failures are only logged
                          */
+                        if app.is_queued() && apps_map.dependencies_ready(&name) {
+                            app.run_scheduled();
+                        }
+
+                        continue;
+                    }
 
+                    if app.get_status() == AppStatus::Init && apps_map.dependencies_ready(&name) {
+                        app.run();
+                    }
+
+                    if app.get_status() == AppStatus::Init && !blocked.contains(&name) {
+                        if let Some(dep) = apps_map.get_failed_dependency(&name) {
+                            log::warn!(
+                                "app \"{}\" will not be started, its dependency \"{}\" has failed",
+                                name,
+                                dep
+                            );
+                            blocked.insert(name.to_owned());
+                        }
+                    }
+
+                    if app.should_restart() {
+                        /*
+                         * The app will be started again by the INIT branch above
+                         * once the backoff delay has passed and all its dependencies are ready.
+                         * If the app is restarted too often, it becomes FAILED
+                         */
+                        app.restart();
+                    }
+
+                    if app.should_shutdown() {
                         state = MainState::Stopping;
                     }
-                }
-                MainState::Stopping => {
-                    if app.get_status() == AppStatus::Running {
-                        let ready =
+
+                    if !app.has_failed() {
+                        handled_failures.remove(&name);
+                    } else if handled_failures.insert(name.to_owned()) {
+                        let dependents = apps_map.get_all_dependents_for(&name);
+
+                        match app.get_failure_policy() {
+                            AppFailurePolicy::Ignore => {
+                                log::warn!("app \"{}\" has failed, ignoring", name);
+                            }
+                            AppFailurePolicy::StopDependents => {
+                                log::warn!(
+                                    "app \"{}\" has failed, stopping its dependents: {:?}",
+                                    name,
+                                    dependents
+                                );
+                                held.extend(dependents);
+                            }
+                            AppFailurePolicy::Restart if app.get_status() == AppStatus::Stopped => {
+                                log::warn!(
+                                    "app \"{}\" has failed, restarting it with its dependents: {:?}",
+                                    name,
+                                    dependents
+                                );
+                                held.extend(dependents);
+                                pending_restarts.insert(name.to_owned());
+                            }
+                            _ => {
+                                /*
+                                 * The app failed, so system operation is not guaranteed
+                                 */
+                                log::error!("app \"{}\" has failed, stopping all apps", name);
+
+                                state = MainState::Stopping;
+                            }
+                        }
+                    }
+
+                    if pending_restarts.contains(&name) {
+                        let dependents = apps_map.get_all_dependents_for(&name);
+                        let ready = dependents.iter().all(|app_name| {
                             apps_map
-                                .get_dependents_for(&app.get_name())
-                                .iter()
-                                .all(|app_name| {
-                                    [AppStatus::Stopped, AppStatus::Init].contains(
-                                        &apps_map.get(app_name).unwrap().borrow().get_status(),
-                                    )
-                                });
+                                .get(app_name)
+                                .unwrap()
+                                .borrow()
+                                .get_status()
+                                .is_inactive()
+                        });
 
                         if ready {
-                            app.stop();
+                            /*
+                             * All dependents are stopped, they will be started again
+                             * as soon as the restarted app is ready
+                             */
+                            for app_name in dependents.iter() {
+                                let mut dependent = apps_map.get(app_name).unwrap().borrow_mut();
+
+                                if dependent.get_status() == AppStatus::Stopped {
+                                    dependent.reset();
+                                }
+
+                                held.remove(app_name);
+                            }
+
+                            pending_restarts.remove(&name);
+                            handled_failures.remove(&name);
+                            app.restart();
                         }
                     }
                 }
+                MainState::Stopping => {
+                    if app.get_status() == AppStatus::Backoff {
+                        app.cancel_restart();
+                    }
+
+                    /*
+                     * Oneshot apps are not signalled, but waited for no longer than
+                     * their stop timeout, so they do not wait for their dependents
+                     */
+                    if app.get_status() == AppStatus::Running
+                        && (app.get_type() == AppType::Oneshot
+                            || apps_map.dependents_stopped(&app.get_name()))
+                    {
+                        app.stop();
+                    }
+                }
             }
         }
 
+        if init_mode {
+            reaper::reap_orphans();
+        }
+
         /*
-         * Finding all apps in the `Stopped` and `Init` statuses means
+         * An app in `Init` status may still be started if all its dependencies are ready
+         * (e.g. a oneshot app it depends on has just completed)
+         */
+        let startable = match state {
+            MainState::Running => apps_map.list().any(|app_rc| {
+                let app = app_rc.borrow();
+                let name = app.get_name();
+
+                /*
+                 * Scheduled apps are waiting for their next run
+                 */
+                !held.contains(&name)
+                    && (app.is_scheduled()
+                        || app.get_status() == AppStatus::Init
+                            && apps_map.dependencies_ready(&name))
+            }),
+            MainState::Stopping => false,
+        };
+
+        /*
+         * Finding all apps in the `Stopped`, `Failed` and `Init` statuses means
          * that apps that were running have already been stopped, and apps that
          * were not running will no longer start.
          *
          * In this case, we can do break
          */
-        if apps_map
-            .every(|app| [AppStatus::Stopped, AppStatus::Init].contains(&app.borrow().get_status()))
-        {
-            /*
-             * If it happens that all apps are stopped or not running,
-             * we stop maestro only in the second cycle.
-             * An update needs to happen to make sure no app wants to run (after exit_code readiness probe, for example)
-             */
-            if !stop_flag {
-                stop_flag = true
-            } else {
-                log::info!("all apps are stopped or have not been started, stopping...");
-                break;
-            }
-        } else {
-            stop_flag = false;
+        if !startable && apps_map.every(|app| app.borrow().get_status().is_inactive()) {
+            log::info!("all apps are stopped or have not been started, stopping...");
+            break;
         }
 
-        thread::sleep(time::Duration::from_millis(POLL_PERIOD));
+        /*
+         * A change of one app may let another app (processed earlier) make progress
+         */
+        if get_snapshot(&apps_map, &state, &held) != snapshot_before {
+            continue;
+        }
+
+        /*
+         * Deadlines that have passed before the apps were updated are already handled,
+         * such apps are waiting for an event
+         */
+        let deadline = apps_map
+            .list()
+            .filter_map(|app_rc| app_rc.borrow().get_next_deadline(iteration_started_at))
+            .min();
+
+        let event = match deadline {
+            Some(deadline) => events.recv_timeout(deadline.saturating_sub(get_now())).ok(),
+            None => events.recv().ok(),
+        };
+
+        for event in event.into_iter().chain(events.try_iter()) {
+            if let Event::Signal(signal) = event {
+                received_signals.push(signal);
+            }
+        }
     }
 }
+
+/*
+ * Everything the decisions of the main loop depend on
+ */
+fn get_snapshot(
+    apps_map: &AppsMap,
+    state: &MainState,
+    held: &HashSet<String>,
+) -> (bool, usize, Vec<(AppStatus, bool)>) {
+    let apps = apps_map
+        .list()
+        .map(|app_rc| {
+            let app = app_rc.borrow();
+
+            (app.get_status(), app.is_ready())
+        })
+        .collect();
+
+    (*state == MainState::Running, held.len(), apps)
+}
@@ -0,0 +1,19 @@
+use std::{io::Error, os::unix::process::CommandExt, process::Command};
+
+/*
+ * Starts the process in a new session, so it has no controlling terminal
+ * and does not belong to the terminal's foreground process group.
+ * Signals generated by the keyboard (like Ctrl+C) are delivered only to maestro then.
+ * The process also becomes the leader of a new process group
+ */
+pub fn detach_from_terminal(command: &mut Command) -> &mut Command {
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(Error::last_os_error());
+            }
+
+            Ok(())
+        })
+    }
+}
@@ -2,10 +2,19 @@ use std::{
     env,
     io::Error,
     process::{Command, ExitStatus, Stdio},
-    time::Instant,
+    thread,
+    time::{Duration, Instant},
 };
 
-pub fn command(cmd: Vec<String>) -> bool {
+use crate::{process::detach_from_terminal, reaper};
+
+const POLL_PERIOD: u64 = 10;
+
+/*
+ * Runs the command and checks its exit status.
+ * The command is killed and considered failed when the `timeout` (ms) expires
+ */
+pub fn command(cmd: Vec<String>, timeout: u32) -> bool {
     if cmd.is_empty() {
         log::warn!("readiness probe command is not presented");
 
@@ -17,19 +26,42 @@ pub fn command(cmd: Vec<String>) -> bool {
     let args = full_command;
     let envs = env::vars();
 
+    let now = Instant::now();
+
     let get_status = || -> Result<ExitStatus, Error> {
-        let exit_status = Command::new(executable)
+        let mut command = Command::new(executable);
+
+        command
             .envs(envs)
             .args(args)
             .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn()?
-            .wait()?;
+            .stderr(Stdio::null());
 
-        Ok(exit_status)
+        let mut child = reaper::spawn(detach_from_terminal(&mut command))?;
+
+        let exit_status = loop {
+            match child.try_wait() {
+                Ok(None) if now.elapsed() >= Duration::from_millis(timeout as u64) => {
+                    /*
+                     * The command is the leader of its own process group (see
+                     * `detach_from_terminal`), its children must not outlive it
+                     */
+                    unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+                    child.wait().ok();
+
+                    break Err(Error::other(format!("timed out after {} ms", timeout)));
+                }
+                Ok(None) => thread::sleep(Duration::from_millis(POLL_PERIOD)),
+                Ok(Some(exit_status)) => break Ok(exit_status),
+                Err(err) => break Err(err),
+            }
+        };
+
+        reaper::release(child.id());
+
+        exit_status
     };
 
-    let now = Instant::now();
     let status = get_status();
     let took = now.elapsed().as_millis();
 
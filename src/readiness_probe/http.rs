@@ -1,15 +1,20 @@
 use std::time::{Duration, Instant};
 
-pub fn http(method: String, url: String) -> bool {
+/*
+ * Sends the request and checks the response status.
+ * The `timeout` (ms) limits the whole request
+ */
+pub fn http(method: String, url: String, timeout: u32) -> bool {
     if url.is_empty() {
         log::warn!("readiness probe url is not presented");
 
         return false;
     }
 
-    let timeout = Duration::from_secs(1);
     let now = Instant::now();
-    let agent = ureq::builder().timeout_connect(timeout).build();
+    let agent = ureq::builder()
+        .timeout(Duration::from_millis(timeout as u64))
+        .build();
     let response = agent.request(method.to_uppercase().as_str(), &url).call();
     let took = now.elapsed().as_millis();
 
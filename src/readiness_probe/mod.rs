@@ -1,5 +1,50 @@
 mod command;
 mod http;
 
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
 pub use command::command;
 pub use http::http;
+
+use crate::events;
+
+/*
+ * A check running on its own thread, so a slow probe never blocks the main loop.
+ * The main loop is woken up as soon as the result is available
+ */
+#[derive(Debug)]
+pub struct PendingCheck(Receiver<bool>);
+
+impl PendingCheck {
+    pub fn spawn<F>(check: F) -> Self
+    where
+        F: FnOnce() -> bool + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            // A check that has panicked is considered failed
+            let result = panic::catch_unwind(AssertUnwindSafe(check)).unwrap_or(false);
+
+            sender.send(result).ok();
+            events::wake();
+        });
+
+        Self(receiver)
+    }
+
+    /*
+     * Returns the result if the check has completed
+     */
+    pub fn poll(&self) -> Option<bool> {
+        match self.0.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(false),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    collections::BTreeSet,
+    io::Error,
+    process::{self, Child, Command},
+    sync::Mutex,
+};
+
+/*
+ * Pids of children spawned by maestro itself (apps, probes, etc.).
+ * Their exit statuses are collected by their owners, the reaper must not touch them
+ */
+static OWNED: Mutex<BTreeSet<u32>> = Mutex::new(BTreeSet::new());
+
+/*
+ * Spawns the command and registers the child as owned.
+ * The owner must call `release` once it has waited for the child
+ */
+pub fn spawn(command: &mut Command) -> Result<Child, Error> {
+    let mut owned = OWNED.lock().unwrap();
+    let child = command.spawn()?;
+
+    owned.insert(child.id());
+
+    Ok(child)
+}
+
+pub fn release(pid: u32) {
+    OWNED.lock().unwrap().remove(&pid);
+}
+
+/*
+ * Makes maestro responsible for orphaned descendants:
+ * PID 1 adopts them anyway, otherwise maestro becomes a child subreaper
+ */
+#[cfg(target_os = "linux")]
+pub fn init() {
+    let pid = process::id();
+
+    if pid != 1 {
+        let code = unsafe { libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0) };
+
+        if code != 0 {
+            log::warn!(
+                "unable to become a child subreaper, {}",
+                Error::last_os_error()
+            );
+        }
+    }
+
+    log::info!("running in init mode, orphaned processes will be reaped");
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn init() {
+    log::warn!(
+        "init mode is not supported on this platform, pid: {}",
+        process::id()
+    );
+}
+
+/*
+ * Returns pids of all children of maestro. Orphans may be re-parented to any of its threads,
+ * so the children of every thread are listed
+ */
+#[cfg(target_os = "linux")]
+fn get_children() -> Vec<libc::pid_t> {
+    let Ok(tasks) = std::fs::read_dir("/proc/self/task") else {
+        return vec![];
+    };
+
+    tasks
+        .flatten()
+        .filter_map(|task| std::fs::read_to_string(task.path().join("children")).ok())
+        .flat_map(|children| {
+            children
+                .split_whitespace()
+                .filter_map(|pid| pid.parse::<libc::pid_t>().ok())
+                .collect::<Vec<libc::pid_t>>()
+        })
+        .collect()
+}
+
+/*
+ * Collects exit statuses of all children that are not owned by anyone (re-parented orphans),
+ * so they do not pile up as zombies. Every child is checked separately,
+ * so an exited child waiting for its owner does not hold the others
+ */
+#[cfg(target_os = "linux")]
+pub fn reap_orphans() {
+    /*
+     * The lock is held while the children are listed,
+     * so a child spawned meanwhile is either registered or not listed
+     */
+    let owned = OWNED.lock().unwrap();
+
+    for pid in get_children() {
+        if owned.contains(&(pid as u32)) {
+            continue;
+        }
+
+        let mut status = 0;
+
+        if unsafe { libc::waitpid(pid, &mut status, libc::WNOHANG) } == pid {
+            log::debug!("reaped orphaned process {}, status {}", pid, status);
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn reap_orphans() {}
@@ -0,0 +1,224 @@
+use libc::c_int;
+
+const NAMES: &[(&str, c_int)] = &[
+    ("HUP", libc::SIGHUP),
+    ("INT", libc::SIGINT),
+    ("QUIT", libc::SIGQUIT),
+    ("ILL", libc::SIGILL),
+    ("TRAP", libc::SIGTRAP),
+    ("ABRT", libc::SIGABRT),
+    ("IOT", libc::SIGIOT),
+    ("BUS", libc::SIGBUS),
+    ("FPE", libc::SIGFPE),
+    ("KILL", libc::SIGKILL),
+    ("USR1", libc::SIGUSR1),
+    ("SEGV", libc::SIGSEGV),
+    ("USR2", libc::SIGUSR2),
+    ("PIPE", libc::SIGPIPE),
+    ("ALRM", libc::SIGALRM),
+    ("TERM", libc::SIGTERM),
+    ("CHLD", libc::SIGCHLD),
+    ("CONT", libc::SIGCONT),
+    ("STOP", libc::SIGSTOP),
+    ("TSTP", libc::SIGTSTP),
+    ("TTIN", libc::SIGTTIN),
+    ("TTOU", libc::SIGTTOU),
+    ("URG", libc::SIGURG),
+    ("XCPU", libc::SIGXCPU),
+    ("XFSZ", libc::SIGXFSZ),
+    ("VTALRM", libc::SIGVTALRM),
+    ("PROF", libc::SIGPROF),
+    ("WINCH", libc::SIGWINCH),
+    ("IO", libc::SIGIO),
+    ("SYS", libc::SIGSYS),
+    #[cfg(target_os = "linux")]
+    ("STKFLT", libc::SIGSTKFLT),
+    #[cfg(target_os = "linux")]
+    ("POLL", libc::SIGPOLL),
+    #[cfg(target_os = "linux")]
+    ("PWR", libc::SIGPWR),
+];
+
+/*
+ * Real-time signals range, they are not numbered portably
+ * and must be referred to as offsets from SIGRTMIN or SIGRTMAX
+ */
+#[cfg(target_os = "linux")]
+fn get_realtime_range() -> Option<(c_int, c_int)> {
+    Some((libc::SIGRTMIN(), libc::SIGRTMAX()))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_realtime_range() -> Option<(c_int, c_int)> {
+    None
+}
+
+fn parse_realtime(name: &str) -> Option<Result<c_int, String>> {
+    let (from_min, rest) = if let Some(rest) = name.strip_prefix("RTMIN") {
+        (true, rest)
+    } else if let Some(rest) = name.strip_prefix("RTMAX") {
+        (false, rest)
+    } else {
+        return None;
+    };
+
+    let (min, max) = match get_realtime_range() {
+        Some(range) => range,
+        None => {
+            return Some(Err(
+                "real-time signals are not supported on this platform".to_string()
+            ))
+        }
+    };
+
+    let offset = match (rest, from_min) {
+        ("", _) => Some(0),
+        (_, true) => rest.strip_prefix('+').and_then(|n| n.parse::<c_int>().ok()),
+        (_, false) => rest
+            .strip_prefix('-')
+            .and_then(|n| n.parse::<c_int>().ok())
+            .map(|n| -n),
+    };
+
+    let offset = match offset {
+        Some(offset) => offset,
+        None => {
+            return Some(Err(format!(
+                "invalid real-time signal \"SIG{}\", expected SIGRTMIN+n or SIGRTMAX-n",
+                name
+            )))
+        }
+    };
+
+    let signal = if from_min { min } else { max } + offset;
+
+    if signal < min || signal > max {
+        return Some(Err(format!(
+            "real-time signal \"SIG{}\" is out of range, there are {} real-time signals",
+            name,
+            max - min + 1
+        )));
+    }
+
+    Some(Ok(signal))
+}
+
+/*
+ * Parses a signal name like "TERM", "sigusr1" or "SIGRTMIN+2", case insensitive
+ */
+pub fn parse(value: &str) -> Result<c_int, String> {
+    let upper = value.trim().to_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+
+    if let Some(result) = parse_realtime(name) {
+        return result;
+    }
+
+    NAMES
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|(_, signal)| *signal)
+        .ok_or(format!("unknown signal name \"{}\"", value))
+}
+
+/*
+ * Checks that the signal number is known on this platform
+ */
+pub fn validate(signal: i64) -> Result<c_int, String> {
+    let error = || format!("invalid signal number {}", signal);
+    let signal = c_int::try_from(signal).map_err(|_| error())?;
+
+    let is_named = NAMES.iter().any(|(_, known)| *known == signal);
+    let is_realtime = get_realtime_range().is_some_and(|(min, max)| (min..=max).contains(&signal));
+
+    if is_named || is_realtime {
+        Ok(signal)
+    } else {
+        Err(error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names() {
+        assert_eq!(parse("TERM"), Ok(libc::SIGTERM));
+        assert_eq!(parse("SIGTERM"), Ok(libc::SIGTERM));
+        assert_eq!(parse("sigusr1"), Ok(libc::SIGUSR1));
+        assert_eq!(parse(" hup "), Ok(libc::SIGHUP));
+        assert_eq!(parse("KILL"), Ok(libc::SIGKILL));
+    }
+
+    #[test]
+    fn rejects_unknown_names() {
+        assert_eq!(
+            parse("TERMINATE"),
+            Err("unknown signal name \"TERMINATE\"".to_string())
+        );
+        assert_eq!(parse("SIG"), Err("unknown signal name \"SIG\"".to_string()));
+        assert_eq!(parse(""), Err("unknown signal name \"\"".to_string()));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parses_realtime_signals() {
+        let (min, max) = get_realtime_range().unwrap();
+
+        assert_eq!(parse("SIGRTMIN"), Ok(min));
+        assert_eq!(parse("rtmin+2"), Ok(min + 2));
+        assert_eq!(parse("SIGRTMAX"), Ok(max));
+        assert_eq!(parse("SIGRTMAX-1"), Ok(max - 1));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn rejects_invalid_realtime_signals() {
+        let (min, max) = get_realtime_range().unwrap();
+
+        assert_eq!(
+            parse("SIGRTMIN-1"),
+            Err(
+                "invalid real-time signal \"SIGRTMIN-1\", expected SIGRTMIN+n or SIGRTMAX-n"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            parse("SIGRTMAX+1"),
+            Err(
+                "invalid real-time signal \"SIGRTMAX+1\", expected SIGRTMIN+n or SIGRTMAX-n"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            parse("SIGRTMIN+100"),
+            Err(format!(
+                "real-time signal \"SIGRTMIN+100\" is out of range, there are {} real-time signals",
+                max - min + 1
+            ))
+        );
+    }
+
+    #[test]
+    fn validates_numbers() {
+        assert_eq!(validate(15), Ok(libc::SIGTERM));
+        assert_eq!(validate(9), Ok(libc::SIGKILL));
+        assert_eq!(validate(0), Err("invalid signal number 0".to_string()));
+        assert_eq!(validate(-1), Err("invalid signal number -1".to_string()));
+        assert_eq!(
+            validate(i64::MAX),
+            Err(format!("invalid signal number {}", i64::MAX))
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn validates_realtime_numbers() {
+        let (min, max) = get_realtime_range().unwrap();
+
+        assert_eq!(validate(min as i64), Ok(min));
+        assert_eq!(validate(max as i64), Ok(max));
+        assert!(validate(max as i64 + 1).is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use std::{
+    collections::hash_map::RandomState,
     env,
+    hash::{BuildHasher, Hasher},
     path::Path,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
@@ -27,3 +29,14 @@ pub fn normalize_path(path_str: String) -> String {
 pub fn get_now() -> Duration {
     SystemTime::now().duration_since(UNIX_EPOCH).unwrap()
 }
+
+/*
+ * Returns a pseudo-random number in the range [0, 1].
+ * It is good enough for jitter and must not be used for anything else
+ */
+pub fn get_random() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(get_now().as_nanos());
+
+    hasher.finish() as f64 / u64::MAX as f64
+}